@@ -0,0 +1,510 @@
+use crate::error::{DownloadError, Result};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, COOKIE};
+use reqwest::RequestBuilder;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// A host name pattern that scopes credentials to the hosts they belong to
+/// 
+/// `docs.example.com` matches that host only, `*.example.com` any of its
+/// subdomains (but not `example.com` itself) and `*` every host. Ports are
+/// not part of the pattern. Credentials are only sent over HTTPS unless the
+/// pattern starts with `http://`, e.g. `http://localhost`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostPattern {
+    pattern: String,
+    /// Whether credentials may also be sent over plain HTTP
+    cleartext: bool,
+}
+
+/// Split a `http://` or `https://` prefix off a host pattern
+fn split_scheme(text: &str) -> (Option<&str>, &str) {
+    match text.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme), rest),
+        None => (None, text),
+    }
+}
+
+impl HostPattern {
+    /// Check if a host name matches the pattern
+    /// 
+    /// The scheme is not checked, see [`HostPattern::allows`].
+    pub fn matches(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        match self.pattern.strip_prefix('*') {
+            Some("") => true,
+            // The suffix starts with a dot, so `*.example.com` never matches `badexample.com`
+            Some(suffix) => host.len() > suffix.len() && host.ends_with(suffix),
+            None => host == self.pattern,
+        }
+    }
+
+    /// Check if credentials scoped to the pattern may be sent to a URL
+    /// 
+    /// The host must match, and the URL must use HTTPS unless the pattern
+    /// names the `http` scheme.
+    pub fn allows(&self, url: &Url) -> bool {
+        let secure = url.scheme() == "https" || (self.cleartext && url.scheme() == "http");
+        secure && url.host_str().is_some_and(|host| self.matches(host))
+    }
+
+    /// Check if text looks like a host pattern rather than a header name
+    /// 
+    /// Used to tell `Host-Pattern: Name: value` header arguments from plain
+    /// `Name: value` ones: host patterns contain a dot or a `*`, or are
+    /// `localhost`, optionally after a `http://` or `https://` prefix.
+    pub fn looks_like(text: &str) -> bool {
+        let text = split_scheme(text.trim()).1;
+        !text.is_empty()
+            && !text.contains(char::is_whitespace)
+            && (text.contains('.') || text.starts_with('*') || text.eq_ignore_ascii_case("localhost"))
+    }
+}
+
+impl FromStr for HostPattern {
+    type Err = DownloadError;
+
+    fn from_str(pattern: &str) -> std::result::Result<Self, Self::Err> {
+        let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+        let invalid = || DownloadError::InvalidConfig(format!("invalid host pattern '{pattern}', expected e.g. docs.example.com or *.example.com"));
+        
+        let (cleartext, pattern) = match split_scheme(&pattern) {
+            (Some("http"), rest) => (true, rest.to_string()),
+            (Some("https"), rest) => (false, rest.to_string()),
+            (Some(_), _) => return Err(invalid()),
+            (None, _) => (false, pattern.clone()),
+        };
+        let name = match pattern.strip_prefix("*.") {
+            Some(name) => name,
+            None if pattern == "*" => return Ok(Self { pattern, cleartext }),
+            None => &pattern,
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '.' | '_'));
+        if !valid {
+            return Err(invalid());
+        }
+        Ok(Self { pattern, cleartext })
+    }
+}
+
+impl fmt::Display for HostPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cleartext {
+            f.write_str("http://")?;
+        }
+        f.write_str(&self.pattern)
+    }
+}
+
+/// Credentials that are only sent to the hosts they belong to
+/// 
+/// llms.txt files link to third-party hosts, so nothing here is ever sent
+/// to a host that was not named explicitly.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Credentials {
+    headers: Vec<(HostPattern, HeaderName, HeaderValue)>,
+    logins: Vec<NetrcLogin>,
+    cookies: Vec<Cookie>,
+}
+
+/// A `machine` entry of a netrc file
+#[derive(Debug, Clone)]
+struct NetrcLogin {
+    machine: String,
+    login: String,
+    password: String,
+}
+
+/// A cookie of a Netscape cookie jar
+#[derive(Debug, Clone)]
+struct Cookie {
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    /// Unix time the cookie expires at, 0 for session cookies
+    expires: u64,
+    name: String,
+    value: String,
+}
+
+impl Credentials {
+    /// Add a header sent to the hosts matching a pattern
+    pub(crate) fn add_header(&mut self, pattern: HostPattern, name: &str, value: &str) -> Result<()> {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| DownloadError::InvalidConfig(format!("invalid header name '{name}'")))?;
+        let mut value = HeaderValue::from_str(value.trim())
+            .map_err(|_| DownloadError::InvalidConfig(format!("invalid value for header '{name}'")))?;
+        // Keeps the value out of debug output
+        value.set_sensitive(true);
+        self.headers.push((pattern, name, value));
+        Ok(())
+    }
+
+    /// Read the `machine` entries of a netrc file for basic authentication
+    /// 
+    /// The `default` entry is ignored, as it would send the password to
+    /// every host an llms.txt links to.
+    pub(crate) fn load_netrc(&mut self, path: &Path) -> Result<()> {
+        let content = read_file(path, "netrc file")?;
+        
+        // macdef bodies run until the next empty line and contain no entries
+        let mut tokens = Vec::new();
+        let mut in_macro = false;
+        for line in content.lines() {
+            if in_macro {
+                in_macro = !line.trim().is_empty();
+                continue;
+            }
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            for word in line.split_whitespace() {
+                if word == "macdef" {
+                    in_macro = true;
+                    break;
+                }
+                tokens.push(word.to_string());
+            }
+        }
+        
+        let mut entry: Option<NetrcLogin> = None;
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            match token.as_str() {
+                "machine" | "default" => {
+                    self.push_login(entry.take());
+                    if token == "machine" {
+                        entry = tokens.next().map(|machine| NetrcLogin {
+                            machine: machine.to_ascii_lowercase(),
+                            login: String::new(),
+                            password: String::new(),
+                        });
+                    }
+                }
+                "login" | "password" | "account" => {
+                    let value = tokens.next().unwrap_or_default();
+                    match (&mut entry, token.as_str()) {
+                        (Some(entry), "login") => entry.login = value,
+                        (Some(entry), "password") => entry.password = value,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.push_login(entry);
+        Ok(())
+    }
+
+    fn push_login(&mut self, entry: Option<NetrcLogin>) {
+        if let Some(entry) = entry.filter(|entry| !entry.login.is_empty()) {
+            self.logins.push(entry);
+        }
+    }
+
+    /// Read the cookies of a Netscape cookie jar, e.g. exported from a browser
+    /// 
+    /// Expired cookies are skipped. Lines prefixed with `#HttpOnly_` are
+    /// cookies, other lines starting with `#` are comments.
+    pub(crate) fn load_cookie_jar(&mut self, path: &Path) -> Result<()> {
+        let content = read_file(path, "cookie jar")?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, include_subdomains, cookie_path, secure, expires, name, value] = fields[..] else {
+                return Err(DownloadError::InvalidConfig(format!(
+                    "invalid cookie jar {} line {}: expected 7 tab-separated fields",
+                    path.display(),
+                    number + 1
+                )));
+            };
+            
+            let expires = expires.trim().parse().unwrap_or(0);
+            if expires != 0 && expires < now {
+                continue;
+            }
+            self.cookies.push(Cookie {
+                domain: domain.trim_start_matches('.').to_ascii_lowercase(),
+                include_subdomains: include_subdomains.eq_ignore_ascii_case("TRUE") || domain.starts_with('.'),
+                path: cookie_path.to_string(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires,
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Add the credentials for a URL's host to a request
+    /// 
+    /// A host-scoped `Authorization` header takes precedence over a netrc
+    /// login for the same host. Over plain HTTP only the headers of patterns
+    /// naming the `http` scheme are sent, never netrc logins or cookies.
+    pub(crate) fn apply(&self, mut request: RequestBuilder, url: &str) -> RequestBuilder {
+        let Ok(url) = Url::parse(url) else {
+            return request;
+        };
+        let Some(host) = url.host_str() else {
+            return request;
+        };
+        
+        let mut has_authorization = false;
+        for (pattern, name, value) in &self.headers {
+            if pattern.allows(&url) {
+                has_authorization |= name == AUTHORIZATION;
+                request = request.header(name.clone(), value.clone());
+            }
+        }
+        if url.scheme() != "https" {
+            return request;
+        }
+        
+        if !has_authorization {
+            if let Some(login) = self.logins.iter().find(|login| login.machine == host) {
+                request = request.basic_auth(&login.login, Some(&login.password));
+            }
+        }
+        
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let cookies: Vec<String> = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(&url, host, now))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect();
+        if !cookies.is_empty() {
+            if let Ok(mut value) = HeaderValue::from_str(&cookies.join("; ")) {
+                value.set_sensitive(true);
+                request = request.header(COOKIE, value);
+            }
+        }
+        
+        request
+    }
+
+    /// Check if following a redirect would send host-scoped headers to a
+    /// host they are not meant for
+    /// 
+    /// Redirects keep all headers except `Authorization` and `Cookie`, so the
+    /// other headers sent to `from` must all be in scope for `to` as well,
+    /// including its scheme.
+    pub(crate) fn leaks_on_redirect(&self, from: &Url, to: &Url) -> bool {
+        self.headers.iter().any(|(pattern, name, _)| {
+            name != AUTHORIZATION && name != COOKIE && pattern.allows(from) && !pattern.allows(to)
+        })
+    }
+}
+
+impl Cookie {
+    /// Check if the cookie is sent with a request, following the domain,
+    /// path and secure rules of RFC 6265
+    fn matches(&self, url: &Url, host: &str, now: u64) -> bool {
+        let domain_matches = host == self.domain
+            || (self.include_subdomains && host.strip_suffix(&self.domain).is_some_and(|prefix| prefix.ends_with('.')));
+        
+        let path = url.path();
+        let path_matches = path == self.path
+            || (path.starts_with(&self.path) && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+        
+        let expired = self.expires != 0 && self.expires < now;
+        domain_matches && path_matches && (!self.secure || url.scheme() == "https") && !expired
+    }
+}
+
+/// Read a credentials file named in the client settings
+fn read_file(path: &Path, what: &str) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| DownloadError::InvalidConfig(format!("could not read {what} {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str) -> HostPattern {
+        pattern.parse().unwrap()
+    }
+
+    fn cookie(domain: &str, include_subdomains: bool, path: &str) -> Cookie {
+        Cookie {
+            domain: domain.to_string(),
+            include_subdomains,
+            path: path.to_string(),
+            secure: false,
+            expires: 0,
+            name: "session".to_string(),
+            value: "secret".to_string(),
+        }
+    }
+
+    fn sent_headers(credentials: &Credentials, url: &str) -> reqwest::header::HeaderMap {
+        let request = credentials.apply(reqwest::Client::new().get(url), url);
+        request.build().unwrap().headers().clone()
+    }
+
+    fn sent(cookie: &Cookie, url: &str) -> bool {
+        let url = Url::parse(url).unwrap();
+        cookie.matches(&url, url.host_str().unwrap(), 1_000)
+    }
+
+    #[test]
+    fn exact_host_pattern() {
+        let pattern = pattern("Docs.Example.com.");
+        
+        assert!(pattern.matches("docs.example.com"));
+        assert!(pattern.matches("DOCS.example.com."));
+        assert!(!pattern.matches("example.com"));
+        assert!(!pattern.matches("api.docs.example.com"));
+    }
+
+    #[test]
+    fn wildcard_host_pattern_matches_subdomains_only() {
+        let pattern = pattern("*.example.com");
+        
+        assert!(pattern.matches("docs.example.com"));
+        assert!(pattern.matches("a.b.example.com"));
+        assert!(!pattern.matches("example.com"));
+        assert!(!pattern.matches("badexample.com"));
+        assert!(!pattern.matches("example.com.evil.test"));
+    }
+
+    #[test]
+    fn star_matches_every_host() {
+        assert!(pattern("*").matches("anything.test"));
+        assert!(pattern(" * ").matches("localhost"));
+    }
+
+    #[test]
+    fn rejects_invalid_host_patterns() {
+        for pattern in ["", "docs.*.com", "exa mple.com", "example.com/path", "**.example.com", "ftp://example.com", "http://"] {
+            assert!(matches!(pattern.parse::<HostPattern>(), Err(DownloadError::InvalidConfig(_))), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn credentials_need_https_unless_the_pattern_names_http() {
+        let url = |url: &str| Url::parse(url).unwrap();
+        
+        for scoped in [pattern("docs.example.com"), pattern("https://docs.example.com")] {
+            assert!(scoped.allows(&url("https://docs.example.com/a.md")));
+            assert!(!scoped.allows(&url("http://docs.example.com/a.md")));
+        }
+        
+        let cleartext = pattern("HTTP://localhost");
+        assert_eq!(cleartext.to_string(), "http://localhost");
+        assert!(cleartext.allows(&url("http://localhost:8080/a.md")));
+        assert!(cleartext.allows(&url("https://localhost/a.md")));
+        assert!(!cleartext.allows(&url("http://example.com/a.md")));
+    }
+
+    #[test]
+    fn no_credentials_over_plain_http() {
+        let mut credentials = Credentials::default();
+        credentials.add_header(pattern("docs.example.com"), "Authorization", "Bearer secret").unwrap();
+        credentials.add_header(pattern("docs.example.com"), "X-Api-Key", "secret").unwrap();
+        credentials.logins.push(NetrcLogin {
+            machine: "docs.example.com".to_string(),
+            login: "user".to_string(),
+            password: "secret".to_string(),
+        });
+        credentials.cookies.push(cookie("docs.example.com", false, "/"));
+        
+        let secure = sent_headers(&credentials, "https://docs.example.com/a.md");
+        assert_eq!(secure[AUTHORIZATION], "Bearer secret");
+        assert_eq!(secure["x-api-key"], "secret");
+        assert_eq!(secure[COOKIE], "session=secret");
+        
+        let cleartext = sent_headers(&credentials, "http://docs.example.com/a.md");
+        assert!(cleartext.get(AUTHORIZATION).is_none());
+        assert!(cleartext.get("x-api-key").is_none());
+        assert!(cleartext.get(COOKIE).is_none());
+        
+        // The netrc login is only used without a scoped Authorization header
+        credentials.headers.clear();
+        assert!(sent_headers(&credentials, "https://docs.example.com/a.md").contains_key(AUTHORIZATION));
+        assert!(!sent_headers(&credentials, "http://docs.example.com/a.md").contains_key(AUTHORIZATION));
+    }
+
+    #[test]
+    fn redirect_to_plain_http_leaks_scoped_headers() {
+        let mut credentials = Credentials::default();
+        credentials.add_header(pattern("docs.example.com"), "X-Api-Key", "secret").unwrap();
+        let url = |url: &str| Url::parse(url).unwrap();
+        
+        assert!(credentials.leaks_on_redirect(&url("https://docs.example.com/a"), &url("http://docs.example.com/a")));
+        assert!(credentials.leaks_on_redirect(&url("https://docs.example.com/a"), &url("https://example.com/a")));
+        assert!(!credentials.leaks_on_redirect(&url("https://docs.example.com/a"), &url("https://docs.example.com/b")));
+        assert!(!credentials.leaks_on_redirect(&url("http://docs.example.com/a"), &url("http://example.com/a")));
+    }
+
+    #[test]
+    fn tells_host_patterns_from_header_names() {
+        assert!(HostPattern::looks_like("docs.example.com"));
+        assert!(HostPattern::looks_like("http://localhost"));
+        assert!(HostPattern::looks_like("*"));
+        assert!(HostPattern::looks_like("localhost"));
+        assert!(!HostPattern::looks_like("Authorization"));
+        assert!(!HostPattern::looks_like("X-Api-Key"));
+    }
+
+    #[test]
+    fn cookie_domain_matching() {
+        let host_only = cookie("example.com", false, "/");
+        assert!(sent(&host_only, "https://example.com/a.md"));
+        assert!(!sent(&host_only, "https://docs.example.com/a.md"));
+        
+        let with_subdomains = cookie("example.com", true, "/");
+        assert!(sent(&with_subdomains, "https://example.com/a.md"));
+        assert!(sent(&with_subdomains, "https://docs.example.com/a.md"));
+        assert!(!sent(&with_subdomains, "https://badexample.com/a.md"));
+        assert!(!sent(&with_subdomains, "https://example.com.evil.test/a.md"));
+    }
+
+    #[test]
+    fn cookie_path_matching() {
+        let cookie = cookie("example.com", false, "/docs");
+        
+        assert!(sent(&cookie, "https://example.com/docs"));
+        assert!(sent(&cookie, "https://example.com/docs/a.md"));
+        assert!(!sent(&cookie, "https://example.com/docsearch/a.md"));
+        assert!(!sent(&cookie, "https://example.com/a.md"));
+        
+        let with_slash = Cookie {
+            path: "/docs/".to_string(),
+            ..cookie
+        };
+        assert!(sent(&with_slash, "https://example.com/docs/a.md"));
+        assert!(!sent(&with_slash, "https://example.com/docs"));
+    }
+
+    #[test]
+    fn secure_and_expired_cookies() {
+        let secure = Cookie {
+            secure: true,
+            ..cookie("example.com", false, "/")
+        };
+        assert!(sent(&secure, "https://example.com/a.md"));
+        assert!(!sent(&secure, "http://example.com/a.md"));
+        
+        let expired = Cookie {
+            expires: 999,
+            ..cookie("example.com", false, "/")
+        };
+        assert!(!sent(&expired, "https://example.com/a.md"));
+    }
+}
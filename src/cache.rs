@@ -0,0 +1,211 @@
+use crate::error::DownloadError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the metadata file stored in each domain directory
+pub const CACHE_FILE_NAME: &str = ".llmsdl-cache.json";
+
+/// Metadata recorded for a downloaded file
+/// 
+/// The validators are sent back to the server on the next run so unchanged
+/// files can be skipped with a `304 Not Modified` response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMetadata {
+    /// `ETag` response header
+    pub etag: Option<String>,
+    /// `Last-Modified` response header
+    pub last_modified: Option<String>,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the file content
+    pub sha256: String,
+    /// `Content-Type` response header, used to pick an extension for
+    /// URLs that have none
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+impl FileMetadata {
+    /// Check if the metadata has any validator usable in a conditional request
+    pub fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+
+    /// Check if the file at `path` still matches the recorded size and SHA-256
+    /// 
+    /// A local copy that was edited or replaced since it was downloaded must
+    /// not be kept just because the server reports no change. The size is
+    /// compared first, so only files that may match are hashed.
+    pub fn matches_file(&self, path: &Path) -> bool {
+        let same_size = fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == self.size);
+        same_size && file_sha256(path).is_ok_and(|sha256| sha256 == self.sha256)
+    }
+}
+
+/// On-disk format of the cache file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    files: BTreeMap<String, FileMetadata>,
+}
+
+/// Per-domain store of file metadata, kept as a JSON sidecar file
+#[derive(Debug, Clone, Default)]
+pub struct DownloadCache {
+    path: PathBuf,
+    files: BTreeMap<String, FileMetadata>,
+}
+
+impl DownloadCache {
+    /// Current version of the cache file format
+    const VERSION: u32 = 1;
+
+    /// Load the cache stored in a domain directory
+    /// 
+    /// A missing, unreadable or outdated cache file results in an empty cache,
+    /// which simply means every file is downloaded again.
+    pub fn load(domain_dir: &Path) -> Self {
+        let path = domain_dir.join(CACHE_FILE_NAME);
+        let files = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .filter(|cache| cache.version == Self::VERSION)
+            .map(|cache| cache.files)
+            .unwrap_or_default();
+        
+        Self { path, files }
+    }
+
+    /// Get the metadata recorded for a URL
+    pub fn get(&self, url: &str) -> Option<&FileMetadata> {
+        self.files.get(url)
+    }
+
+    /// Record the metadata of a downloaded file
+    pub fn insert(&mut self, url: String, metadata: FileMetadata) {
+        self.files.insert(url, metadata);
+    }
+
+    /// Forget the metadata of a file that is no longer listed
+    pub fn remove(&mut self, url: &str) {
+        self.files.remove(url);
+    }
+
+    /// Write the cache back to the domain directory
+    pub fn save(&self) -> Result<(), DownloadError> {
+        let cache = CacheFile {
+            version: Self::VERSION,
+            files: self.files.clone(),
+        };
+        let content = serde_json::to_string_pretty(&cache)
+            .map_err(|e| DownloadError::ParseError(format!("Failed to serialize download cache: {e}")))?;
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+/// Validators of a partially downloaded file
+/// 
+/// Stored as a small JSON file next to the `.part` file so an interrupted
+/// download can later be resumed with a `Range` / `If-Range` request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialDownload {
+    /// `ETag` of the response the partial file came from
+    pub etag: Option<String>,
+    /// `Last-Modified` of the response the partial file came from
+    pub last_modified: Option<String>,
+}
+
+impl PartialDownload {
+    /// Load the validators stored for a partial file
+    pub fn load(partial_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(Self::sidecar_path(partial_path)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store the validators next to a partial file
+    pub fn save(&self, partial_path: &Path) -> Result<(), DownloadError> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| DownloadError::ParseError(format!("Failed to serialize partial download state: {e}")))?;
+        fs::write(Self::sidecar_path(partial_path), content)?;
+        Ok(())
+    }
+
+    /// Remove a partial file along with its validators
+    pub fn discard(partial_path: &Path) {
+        let _ = fs::remove_file(partial_path);
+        Self::forget(partial_path);
+    }
+
+    /// Remove the validators of a partial file once it is complete
+    pub fn forget(partial_path: &Path) {
+        let _ = fs::remove_file(Self::sidecar_path(partial_path));
+    }
+
+    /// Value for the `If-Range` header
+    /// 
+    /// Weak ETags cannot be used for range requests, so Last-Modified is
+    /// used instead when the ETag is weak or missing.
+    pub fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+
+    /// Path of the sidecar file for a partial file
+    fn sidecar_path(partial_path: &Path) -> PathBuf {
+        let mut file_name = partial_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".json");
+        partial_path.with_file_name(file_name)
+    }
+}
+
+/// Compute the hex-encoded SHA-256 of some content
+pub fn sha256_hex(content: &[u8]) -> String {
+    to_hex(&Sha256::digest(content))
+}
+
+/// Hash a file without reading it into memory at once
+fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Encode bytes as a lowercase hex string
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_file_compares_size_and_content() {
+        let path = std::env::temp_dir().join(format!("llmsdl-cache-{}.md", std::process::id()));
+        fs::write(&path, "alpha").unwrap();
+        let metadata = FileMetadata {
+            etag: Some("\"1\"".to_string()),
+            last_modified: None,
+            size: 5,
+            sha256: sha256_hex(b"alpha"),
+            content_type: None,
+        };
+        assert!(metadata.matches_file(&path));
+        
+        // Same size, different content
+        fs::write(&path, "gamma").unwrap();
+        assert!(!metadata.matches_file(&path));
+        
+        fs::write(&path, "alphabet").unwrap();
+        assert!(!metadata.matches_file(&path));
+        
+        fs::remove_file(&path).unwrap();
+        assert!(!metadata.matches_file(&path));
+    }
+}
@@ -0,0 +1,209 @@
+// llmsdl.toml: defaults for the command line and named site profiles
+
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use llmsdl::{DownloadError, Rate};
+
+use crate::{BearerTokenEnv, CrossOrigin, FailOn, HeaderArg, Prune, ReportKind};
+
+/// File name of the config file
+pub const CONFIG_FILE: &str = "llmsdl.toml";
+
+/// Settings read from llmsdl.toml
+/// 
+/// Keys are named after the long command line flags, and flags that can be
+/// repeated take a list. Flags given on the command line override the
+/// config values.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Defaults for every command
+    pub defaults: Defaults,
+    /// Site profiles run by `llmsdl sync`, by name
+    pub sites: BTreeMap<String, SiteProfile>,
+}
+
+/// The `[defaults]` table: download and HTTP settings
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Defaults {
+    // Download settings, as in DownloadOptions
+    /// Output root; site profiles are saved in subdirectories of it
+    pub output: Option<PathBuf>,
+    pub threads: Option<usize>,
+    pub site_threads: Option<usize>,
+    pub parallel_sites: Option<usize>,
+    pub cross_origin: Option<CrossOrigin>,
+    pub respect_robots: Option<bool>,
+    pub no_cache: Option<bool>,
+    pub prune: Option<Prune>,
+    pub report: Option<ReportKind>,
+    pub fail_on: Option<FailOn>,
+    
+    // HTTP settings, as in HttpArgs
+    #[serde(deserialize_with = "duration")]
+    pub connect_timeout: Option<Duration>,
+    #[serde(deserialize_with = "duration")]
+    pub read_timeout: Option<Duration>,
+    #[serde(deserialize_with = "duration")]
+    pub file_timeout: Option<Duration>,
+    pub retries: Option<u32>,
+    #[serde(deserialize_with = "duration")]
+    pub backoff_base: Option<Duration>,
+    #[serde(deserialize_with = "duration")]
+    pub backoff_cap: Option<Duration>,
+    pub user_agent: Option<String>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub header: Vec<HeaderArg>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub bearer_token_env: Vec<BearerTokenEnv>,
+    pub netrc: Option<bool>,
+    pub netrc_file: Option<PathBuf>,
+    pub cookie_jar: Option<PathBuf>,
+    #[serde(deserialize_with = "parsed")]
+    pub rate: Option<Rate>,
+    pub host_concurrency: Option<usize>,
+    #[serde(deserialize_with = "duration")]
+    pub min_delay: Option<Duration>,
+    pub proxy: Option<String>,
+    pub no_proxy: Option<String>,
+    pub ca_cert: Vec<PathBuf>,
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    pub insecure: Option<bool>,
+}
+
+/// A `[sites.<name>]` table: a website and how to download it
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SiteProfile {
+    /// The website URL
+    pub url: String,
+    /// Subdirectory of the output root to save the site in
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+    /// Headers sent to the site's host, or to the hosts of their pattern
+    #[serde(default, deserialize_with = "parsed_list")]
+    pub header: Vec<HeaderArg>,
+    /// Bearer tokens sent to the site's host, or to the hosts of their pattern
+    #[serde(default, deserialize_with = "parsed_list")]
+    pub bearer_token_env: Vec<BearerTokenEnv>,
+    #[serde(default)]
+    pub section: Vec<String>,
+    #[serde(default)]
+    pub exclude_section: Vec<String>,
+    #[serde(default)]
+    pub skip_optional: Option<bool>,
+    #[serde(default)]
+    pub full: bool,
+    #[serde(default)]
+    pub prefer_full: bool,
+    #[serde(default)]
+    pub split: Option<bool>,
+}
+
+impl Config {
+    /// Find and read the config file
+    /// 
+    /// An explicit `path` must exist. Otherwise `./llmsdl.toml` is used,
+    /// then `$XDG_CONFIG_HOME/llmsdl/llmsdl.toml` (`~/.config/llmsdl/llmsdl.toml`
+    /// when XDG_CONFIG_HOME is not set); without either there is no config.
+    /// 
+    /// # Arguments
+    /// * `path` - The file given with `--config`, if any
+    /// 
+    /// # Returns
+    /// * `Result<Option<Config>, DownloadError>` - The config, if a file was found
+    pub fn load(path: Option<&Path>) -> Result<Option<Config>, DownloadError> {
+        if let Some(path) = path {
+            return Config::read(path).map(Some);
+        }
+        
+        let local = PathBuf::from(CONFIG_FILE);
+        let found = std::iter::once(local)
+            .chain(user_config_path())
+            .find(|path| path.is_file());
+        found.map(|path| Config::read(&path)).transpose()
+    }
+
+    /// Read a config file
+    /// 
+    /// Relative paths in `[defaults]` are taken relative to the directory of
+    /// the file, so a project's config works from anywhere.
+    fn read(path: &Path) -> Result<Config, DownloadError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            DownloadError::InvalidConfig(format!("could not read config file {}: {e}", path.display()))
+        })?;
+        let mut config: Config = toml::from_str(&content)
+            .map_err(|e| DownloadError::InvalidConfig(format!("invalid config file {}: {e}", path.display())))?;
+        
+        let base = path.parent().unwrap_or(Path::new(""));
+        let defaults = &mut config.defaults;
+        let paths = defaults
+            .output
+            .iter_mut()
+            .chain(defaults.netrc_file.iter_mut())
+            .chain(defaults.cookie_jar.iter_mut())
+            .chain(defaults.ca_cert.iter_mut())
+            .chain(defaults.client_cert.iter_mut())
+            .chain(defaults.client_key.iter_mut());
+        for path in paths {
+            *path = base.join(&*path);
+        }
+        Ok(config)
+    }
+
+    /// Get a site profile by name
+    pub fn profile(&self, name: &str) -> Result<&SiteProfile, DownloadError> {
+        self.sites.get(name).ok_or_else(|| {
+            let known = self.sites.keys().map(String::as_str).collect::<Vec<_>>().join(", ");
+            DownloadError::InvalidConfig(format!("no site profile named '{name}' (profiles: {known})"))
+        })
+    }
+}
+
+/// Path of the config file in the user's config directory
+fn user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")))?;
+    Some(config_home.join("llmsdl").join(CONFIG_FILE))
+}
+
+/// Deserialize a duration such as "10s" or "1m 30s"
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    humantime::parse_duration(&text)
+        .map(Some)
+        .map_err(|e| serde::de::Error::custom(format!("invalid duration '{text}': {e}")))
+}
+
+/// Deserialize a value written like its command line flag
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+/// Deserialize a list of values written like their command line flag
+fn parsed_list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|text| text.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
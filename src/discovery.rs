@@ -0,0 +1,456 @@
+use crate::error::{DownloadError, ErrorKind, Result};
+use crate::http_client::HttpClient;
+use reqwest::header::{HeaderMap, LINK};
+use url::Url;
+
+/// Header some sites use to advertise their llms.txt, besides `Link`
+const LLMS_TXT_HEADER: &str = "x-llms-txt";
+
+/// Index files a website can publish
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFile {
+    /// `llms.txt`, a list of links to documentation files
+    LlmsTxt,
+    /// `llms-full.txt`, all documentation in a single file
+    LlmsFullTxt,
+}
+
+impl IndexFile {
+    /// File name of the index file
+    pub fn file_name(self) -> &'static str {
+        match self {
+            IndexFile::LlmsTxt => "llms.txt",
+            IndexFile::LlmsFullTxt => "llms-full.txt",
+        }
+    }
+
+    /// `rel` value of `<link>` elements and `Link` headers pointing to the file
+    fn link_rel(self) -> &'static str {
+        match self {
+            IndexFile::LlmsTxt => "llms-txt",
+            IndexFile::LlmsFullTxt => "llms-full-txt",
+        }
+    }
+
+    /// Get the index file a URL points to directly, if any
+    /// 
+    /// URLs whose path ends in `llms.txt` or `llms-full.txt` are used as
+    /// they are instead of being searched from.
+    pub fn of_url(url: &str) -> Option<Self> {
+        let path = Url::parse(url).ok()?.path().to_ascii_lowercase();
+        if path.ends_with("llms-full.txt") {
+            Some(IndexFile::LlmsFullTxt)
+        } else if path.ends_with("llms.txt") {
+            Some(IndexFile::LlmsTxt)
+        } else {
+            None
+        }
+    }
+}
+
+/// An index file found by [`discover`]
+#[derive(Debug, Clone)]
+pub struct Discovered {
+    /// URL the file was found at
+    pub url: String,
+    /// Content of the file, unless only its presence was checked
+    pub content: Option<String>,
+    /// Candidates that were tried before it, with why they failed
+    pub tried: Vec<(String, ErrorKind)>,
+}
+
+/// Get the locations to look for an index file at, in order
+/// 
+/// A URL pointing at `llms.txt` or `llms-full.txt` is used directly, and
+/// the other index file is only looked for next to it. Otherwise the file
+/// is looked for in the directory of the URL and each parent directory up
+/// to the root, then in `/.well-known/`.
+/// 
+/// # Arguments
+/// * `url` - The website URL, e.g. `https://example.com/docs/v2`
+/// * `file` - The index file to look for
+/// 
+/// # Returns
+/// * `Result<Vec<String>>` - Candidate URLs, most specific first
+pub fn candidates(url: &str, file: IndexFile) -> Result<Vec<String>> {
+    let mut url = Url::parse(url)?;
+    url.set_query(None);
+    url.set_fragment(None);
+    
+    if let Some(direct) = IndexFile::of_url(url.as_str()) {
+        if direct != file {
+            url = url.join(file.file_name())?;
+        }
+        return Ok(vec![url.to_string()]);
+    }
+    
+    let mut segments: Vec<String> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    // A trailing file name such as `index.html` is not a directory
+    if !url.path().ends_with('/') && segments.last().is_some_and(|segment| segment.contains('.')) {
+        segments.pop();
+    }
+    
+    let mut candidates = Vec::new();
+    loop {
+        let directory = segments.iter().map(|segment| format!("{segment}/")).collect::<String>();
+        candidates.push(url.join(&format!("/{directory}{}", file.file_name()))?.to_string());
+        if segments.pop().is_none() {
+            break;
+        }
+    }
+    candidates.push(url.join(&format!("/.well-known/{}", file.file_name()))?.to_string());
+    Ok(candidates)
+}
+
+/// Find a website's index file
+/// 
+/// Tries the [`candidates`] in order, then the locations advertised by the
+/// page at `url` (and the site's homepage) with a `<link rel="llms-txt">`
+/// element, a `Link: <...>; rel="llms-txt"` header or an `X-Llms-Txt`
+/// header. `llms-full.txt` is advertised with `rel="llms-full-txt"`.
+/// The search stops early on errors without an HTTP status, such as DNS or
+/// connection failures.
+/// 
+/// # Arguments
+/// * `client` - The client to send requests with
+/// * `url` - The website URL, or the URL of the index file itself
+/// * `file` - The index file to look for
+/// * `fetch` - Whether to fetch the content, or only check that the file exists
+/// 
+/// # Returns
+/// * `Result<Discovered>` - Where the file was found, or
+///   [`DownloadError::LlmsTxtUnavailable`] listing every candidate tried
+pub async fn discover(client: &HttpClient, url: &str, file: IndexFile, fetch: bool) -> Result<Discovered> {
+    let mut candidates = candidates(url, file)?;
+    let mut failures: Vec<(String, DownloadError)> = Vec::new();
+    let mut hints_checked = IndexFile::of_url(url).is_some();
+    let mut next = 0;
+    
+    while next < candidates.len() || !hints_checked {
+        if next == candidates.len() {
+            hints_checked = true;
+            for hint in advertised(client, url, file).await {
+                if !candidates.contains(&hint) {
+                    candidates.push(hint);
+                }
+            }
+            continue;
+        }
+        
+        let candidate = candidates[next].clone();
+        next += 1;
+        let found = if fetch {
+            client.fetch_content(&candidate).await.map(Some)
+        } else {
+            client.check_url(&candidate).await.map(|()| None)
+        };
+        match found {
+            Ok(content) => {
+                return Ok(Discovered {
+                    url: candidate,
+                    content,
+                    tried: failures.into_iter().map(|(url, e)| (url, e.kind())).collect(),
+                });
+            }
+            // Without an HTTP status the host cannot be reached, other locations on it won't do better
+            Err(e) if !matches!(e, DownloadError::FileNotFound(_) | DownloadError::HttpError { .. }) => {
+                failures.push((candidate, e));
+                break;
+            }
+            Err(e) => failures.push((candidate, e)),
+        }
+    }
+    
+    Err(unavailable(failures))
+}
+
+/// Build the error for an index file that was not found anywhere
+/// 
+/// Reports the first failure that is not a plain "not found", e.g. a 401
+/// or a TLS error, since that is what needs fixing, and the first candidate
+/// otherwise.
+fn unavailable(failures: Vec<(String, DownloadError)>) -> DownloadError {
+    let tried: Vec<String> = failures.iter().map(|(url, _)| url.clone()).collect();
+    let position = failures
+        .iter()
+        .position(|(_, e)| e.kind() != ErrorKind::NotFound)
+        .unwrap_or(0);
+    
+    match failures.into_iter().nth(position) {
+        Some((url, source)) => DownloadError::LlmsTxtUnavailable {
+            url,
+            source: Box::new(source),
+            tried,
+        },
+        None => DownloadError::InvalidUrl("no location to look for llms.txt".to_string()),
+    }
+}
+
+/// Collect the index file locations advertised by a page and the site's homepage
+async fn advertised(client: &HttpClient, url: &str, file: IndexFile) -> Vec<String> {
+    let Ok(page) = Url::parse(url) else {
+        return Vec::new();
+    };
+    let mut pages = vec![page.clone()];
+    if let Ok(homepage) = page.join("/") {
+        if homepage != page {
+            pages.push(homepage);
+        }
+    }
+    
+    let mut hints = Vec::new();
+    for page in pages {
+        // Pages without hints or that cannot be fetched are no reason to give up
+        let Ok((headers, body)) = client.fetch_page(page.as_str()).await else {
+            continue;
+        };
+        let hrefs = header_hints(&headers, file).into_iter().chain(html_hints(&body, file));
+        for href in hrefs {
+            if let Ok(hint) = page.join(&href) {
+                let hint = hint.to_string();
+                if !hints.contains(&hint) {
+                    hints.push(hint);
+                }
+            }
+        }
+    }
+    hints
+}
+
+/// Get the locations advertised by `Link` and `X-Llms-Txt` response headers
+fn header_hints(headers: &HeaderMap, file: IndexFile) -> Vec<String> {
+    let mut hints = Vec::new();
+    
+    for value in headers.get_all(LINK).iter().filter_map(|value| value.to_str().ok()) {
+        for link in value.split(',') {
+            let mut parts = link.split(';');
+            let target = parts.next().unwrap_or_default().trim();
+            let Some(target) = target.strip_prefix('<').and_then(|target| target.strip_suffix('>')) else {
+                continue;
+            };
+            let has_rel = parts.any(|param| {
+                let Some((name, value)) = param.split_once('=') else {
+                    return false;
+                };
+                name.trim().eq_ignore_ascii_case("rel") && rel_contains(value.trim().trim_matches('"'), file)
+            });
+            if has_rel {
+                hints.push(target.to_string());
+            }
+        }
+    }
+    
+    if file == IndexFile::LlmsTxt {
+        hints.extend(
+            headers
+                .get_all(LLMS_TXT_HEADER)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+        );
+    }
+    hints
+}
+
+/// Get the locations advertised by `<link rel="llms-txt" href="...">` elements
+fn html_hints(html: &str, file: IndexFile) -> Vec<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut hints = Vec::new();
+    let mut rest = 0;
+    
+    while let Some(start) = lower[rest..].find("<link").map(|start| start + rest) {
+        let end = lower[start..].find('>').map_or(lower.len(), |end| start + end);
+        // Attribute names are matched in lowercase, values are taken as written
+        let attributes = parse_attributes(&html[start + "<link".len()..end]);
+        let rel = attributes.iter().find(|(name, _)| name == "rel");
+        let href = attributes.iter().find(|(name, _)| name == "href");
+        if let (Some((_, rel)), Some((_, href))) = (rel, href) {
+            if rel_contains(rel, file) && !href.is_empty() {
+                hints.push(href.clone());
+            }
+        }
+        rest = end;
+    }
+    hints
+}
+
+/// Check if a space-separated `rel` value names the index file
+fn rel_contains(rel: &str, file: IndexFile) -> bool {
+    rel.split_whitespace().any(|rel| rel.eq_ignore_ascii_case(file.link_rel()))
+}
+
+/// Parse the attributes of an HTML tag into lowercase names and their values
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = tag.trim_end_matches('/').char_indices().peekable();
+    
+    while let Some(&(start, ch)) = chars.peek() {
+        if ch.is_whitespace() || ch == '/' {
+            chars.next();
+            continue;
+        }
+        
+        let mut name_end = start;
+        while let Some(&(index, ch)) = chars.peek() {
+            if ch.is_whitespace() || ch == '=' {
+                break;
+            }
+            name_end = index + ch.len_utf8();
+            chars.next();
+        }
+        let name = tag[start..name_end].to_ascii_lowercase();
+        
+        while chars.peek().is_some_and(|(_, ch)| ch.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().map(|(_, ch)| *ch) != Some('=') {
+            attributes.push((name, String::new()));
+            continue;
+        }
+        chars.next();
+        while chars.peek().is_some_and(|(_, ch)| ch.is_whitespace()) {
+            chars.next();
+        }
+        
+        let mut value = String::new();
+        match chars.peek().map(|(_, ch)| *ch) {
+            Some(quote @ ('"' | '\'')) => {
+                chars.next();
+                for (_, ch) in chars.by_ref() {
+                    if ch == quote {
+                        break;
+                    }
+                    value.push(ch);
+                }
+            }
+            _ => {
+                while let Some(&(_, ch)) = chars.peek() {
+                    if ch.is_whitespace() {
+                        break;
+                    }
+                    value.push(ch);
+                    chars.next();
+                }
+            }
+        }
+        attributes.push((name, value.trim().to_string()));
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn candidates_walk_up_to_the_root_then_well_known() {
+        assert_eq!(
+            candidates("https://example.com/docs/v2?lang=en#intro", IndexFile::LlmsTxt).unwrap(),
+            [
+                "https://example.com/docs/v2/llms.txt",
+                "https://example.com/docs/llms.txt",
+                "https://example.com/llms.txt",
+                "https://example.com/.well-known/llms.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn candidates_skip_a_trailing_file_name() {
+        assert_eq!(
+            candidates("https://example.com/docs/index.html", IndexFile::LlmsFullTxt).unwrap(),
+            [
+                "https://example.com/docs/llms-full.txt",
+                "https://example.com/llms-full.txt",
+                "https://example.com/.well-known/llms-full.txt",
+            ]
+        );
+        assert_eq!(
+            candidates("https://example.com", IndexFile::LlmsTxt).unwrap(),
+            ["https://example.com/llms.txt", "https://example.com/.well-known/llms.txt"]
+        );
+    }
+
+    #[test]
+    fn candidates_keep_a_dotted_directory_with_a_trailing_slash() {
+        assert_eq!(
+            candidates("https://example.com/docs/v1.2/", IndexFile::LlmsTxt).unwrap(),
+            [
+                "https://example.com/docs/v1.2/llms.txt",
+                "https://example.com/docs/llms.txt",
+                "https://example.com/llms.txt",
+                "https://example.com/.well-known/llms.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn candidates_use_a_direct_index_file_url() {
+        assert_eq!(
+            candidates("https://example.com/docs/llms.txt", IndexFile::LlmsTxt).unwrap(),
+            ["https://example.com/docs/llms.txt"]
+        );
+        // The other index file is only looked for next to it
+        assert_eq!(
+            candidates("https://example.com/docs/LLMS.txt", IndexFile::LlmsFullTxt).unwrap(),
+            ["https://example.com/docs/llms-full.txt"]
+        );
+    }
+
+    #[test]
+    fn candidates_reject_invalid_urls() {
+        assert!(candidates("not a url", IndexFile::LlmsTxt).is_err());
+    }
+
+    #[test]
+    fn html_hints_read_link_elements() {
+        let html = r#"<html><head>
+            <LINK rel="stylesheet" href="/style.css">
+            <link href='/docs/llms.txt' REL="alternate llms-txt">
+            <link rel=llms-full-txt href=/docs/llms-full.txt />
+            <link rel="llms-txt" href="">
+        </head></html>"#;
+        
+        assert_eq!(html_hints(html, IndexFile::LlmsTxt), ["/docs/llms.txt"]);
+        assert_eq!(html_hints(html, IndexFile::LlmsFullTxt), ["/docs/llms-full.txt"]);
+        assert!(html_hints("<p>no links</p>", IndexFile::LlmsTxt).is_empty());
+    }
+
+    #[test]
+    fn html_hints_keep_the_case_of_values() {
+        let html = r#"<link rel="llms-txt" href="/Docs/LLMS.txt"><link rel="llms-txt" href="https://cdn.example/llms.txt">"#;
+        assert_eq!(html_hints(html, IndexFile::LlmsTxt), ["/Docs/LLMS.txt", "https://cdn.example/llms.txt"]);
+    }
+
+    #[test]
+    fn parses_attributes() {
+        assert_eq!(
+            parse_attributes(r#" rel = "a b" HREF=/x data-flag title='it"s' /"#),
+            [
+                ("rel".to_string(), "a b".to_string()),
+                ("href".to_string(), "/x".to_string()),
+                ("data-flag".to_string(), String::new()),
+                ("title".to_string(), "it\"s".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn header_hints_read_link_and_x_llms_txt_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            LINK,
+            HeaderValue::from_static(r#"</style.css>; rel=preload, </docs/llms.txt>; rel="llms-txt""#),
+        );
+        headers.append(LINK, HeaderValue::from_static("</docs/llms-full.txt>; REL=llms-full-txt"));
+        headers.append(LLMS_TXT_HEADER, HeaderValue::from_static(" /other/llms.txt "));
+        
+        assert_eq!(header_hints(&headers, IndexFile::LlmsTxt), ["/docs/llms.txt", "/other/llms.txt"]);
+        assert_eq!(header_hints(&headers, IndexFile::LlmsFullTxt), ["/docs/llms-full.txt"]);
+    }
+}
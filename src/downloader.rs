@@ -0,0 +1,1217 @@
+use crate::cache::{sha256_hex, DownloadCache, FileMetadata};
+use crate::discovery::{discover, Discovered, IndexFile};
+use crate::error::{BatchResult, DownloadError, DownloadResult, PlannedFile, SiteResult};
+use crate::file_manager::{
+    create_local_directory, extension_for_content_type, local_directory, relative_path, slugify, CrossOriginPolicy,
+    MappingManifest, PathMapper,
+};
+use crate::events::{DownloadEvent, EventSink, FinishOutcome};
+use crate::http_client::{DownloadOutcome, HttpClient};
+use crate::manifest::Manifest;
+use crate::parser::{self, parse_llms_txt, LlmsTxtEntry, SectionFilter};
+use crate::sync::{prune_file, Changes, PruneMode};
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::Semaphore;
+use url::Url;
+
+/// Reason recorded for files skipped because of robots.txt
+const ROBOTS_SKIP_REASON: &str = "Disallowed by robots.txt";
+
+/// Which index file to fetch from the website
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourceMode {
+    /// Fetch llms.txt and download every file it lists
+    #[default]
+    LlmsTxt,
+    /// Fetch llms-full.txt only
+    Full,
+    /// Fetch llms-full.txt, falling back to llms.txt when it is missing
+    PreferFull,
+}
+
+/// Downloads the documentation files listed in a website's llms.txt
+/// 
+/// Create one with [`Downloader::builder`], then call [`Downloader::download`]
+/// for each website. A downloader can be reused for any number of websites.
+#[derive(Clone)]
+pub struct Downloader {
+    client: HttpClient,
+    max_concurrent: usize,
+    /// Limits downloads across all websites, shared by all clones
+    total_slots: Option<Arc<Semaphore>>,
+    output_dir: PathBuf,
+    filter: SectionFilter,
+    source: SourceMode,
+    split: bool,
+    use_cache: bool,
+    cross_origin: CrossOriginPolicy,
+    respect_robots: bool,
+    prune: PruneMode,
+    dry_run: bool,
+    show_progress: bool,
+    events: Option<Arc<dyn EventSink>>,
+}
+
+/// Builder for [`Downloader`]
+#[derive(Clone)]
+pub struct DownloaderBuilder {
+    client: Option<HttpClient>,
+    max_concurrent: usize,
+    total_slots: Option<Arc<Semaphore>>,
+    output_dir: PathBuf,
+    filter: SectionFilter,
+    source: SourceMode,
+    split: bool,
+    use_cache: bool,
+    cross_origin: CrossOriginPolicy,
+    respect_robots: bool,
+    prune: PruneMode,
+    dry_run: bool,
+    show_progress: bool,
+    events: Option<Arc<dyn EventSink>>,
+}
+
+impl DownloaderBuilder {
+    /// Create a builder with default settings
+    /// 
+    /// Defaults to 5 concurrent downloads into `downloads/`, fetching llms.txt
+    /// without section filters and without printing progress.
+    pub fn new() -> Self {
+        Self {
+            client: None,
+            max_concurrent: 5,
+            total_slots: None,
+            output_dir: PathBuf::from("downloads"),
+            filter: SectionFilter::default(),
+            source: SourceMode::default(),
+            split: false,
+            use_cache: true,
+            cross_origin: CrossOriginPolicy::default(),
+            respect_robots: false,
+            prune: PruneMode::default(),
+            dry_run: false,
+            show_progress: false,
+            events: None,
+        }
+    }
+
+    /// Use the given HTTP client instead of a default one
+    pub fn client(mut self, client: HttpClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set the maximum number of concurrent downloads per website (at least 1)
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// Set the maximum number of concurrent downloads across all websites (at least 1)
+    /// 
+    /// The limit is shared by every website downloaded with the downloader and
+    /// its clones, e.g. by [`Downloader::download_all`], and by all downloaders
+    /// built from clones of this builder. Unlimited by default.
+    pub fn total_concurrent(mut self, total_concurrent: usize) -> Self {
+        self.total_slots = Some(Arc::new(Semaphore::new(total_concurrent.max(1))));
+        self
+    }
+
+    /// Set the output directory; files are saved in a per-domain subdirectory
+    pub fn output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    /// Only download the sections accepted by the filter
+    pub fn filter(mut self, filter: SectionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Choose between llms.txt and llms-full.txt
+    pub fn source(mut self, source: SourceMode) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Split llms-full.txt back into one file per document
+    pub fn split(mut self, split: bool) -> Self {
+        self.split = split;
+        self
+    }
+
+    /// Skip files that have not changed since the previous run (enabled by default)
+    /// 
+    /// ETag and Last-Modified values are stored in a sidecar file in each
+    /// domain directory and sent back as conditional request headers.
+    pub fn use_cache(mut self, use_cache: bool) -> Self {
+        self.use_cache = use_cache;
+        self
+    }
+
+    /// Choose how files hosted on other hosts than llms.txt are handled
+    /// 
+    /// By default they are saved under `_external/<host>/` in the domain directory.
+    pub fn cross_origin(mut self, cross_origin: CrossOriginPolicy) -> Self {
+        self.cross_origin = cross_origin;
+        self
+    }
+
+    /// Skip files that the robots.txt of their host disallows
+    /// 
+    /// robots.txt is evaluated for the client's User-Agent and its
+    /// `Crawl-delay` is honored. Skipped files are listed in
+    /// [`DownloadResult::skipped`].
+    pub fn respect_robots(mut self, respect_robots: bool) -> Self {
+        self.respect_robots = respect_robots;
+        self
+    }
+
+    /// Choose what happens to files of the previous run that are no longer listed
+    /// 
+    /// Every run compares its files with the `manifest.json` of the previous
+    /// run and reports the differences in [`DownloadResult::changes`]. By
+    /// default files that are no longer listed are kept.
+    pub fn prune(mut self, prune: PruneMode) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Only report what a run would change, without downloading or writing anything
+    /// 
+    /// The index file is still fetched, filtered and mapped to local paths.
+    /// The files that would be downloaded are listed in
+    /// [`DownloadResult::planned`] and compared with the previous run's manifest.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Print status messages and progress bars to the terminal
+    pub fn show_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    /// Report start, progress, retry and finish events for every file
+    pub fn events(mut self, events: Arc<dyn EventSink>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Build the downloader
+    pub fn build(self) -> Downloader {
+        let client = self.client.unwrap_or_default();
+        Downloader {
+            client: match &self.events {
+                Some(events) => client.with_events(Arc::clone(events)),
+                // Retry notices are part of the progress output
+                None if self.show_progress => client.with_status(Arc::new(|message: &str| eprintln!("{message}"))),
+                None => client,
+            },
+            max_concurrent: self.max_concurrent,
+            total_slots: self.total_slots,
+            output_dir: self.output_dir,
+            filter: self.filter,
+            source: self.source,
+            split: self.split,
+            use_cache: self.use_cache,
+            cross_origin: self.cross_origin,
+            respect_robots: self.respect_robots,
+            prune: self.prune,
+            dry_run: self.dry_run,
+            show_progress: self.show_progress,
+            events: self.events,
+        }
+    }
+}
+
+impl Default for DownloaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Downloader {
+    /// Create a builder for configuring a downloader
+    pub fn builder() -> DownloaderBuilder {
+        DownloaderBuilder::new()
+    }
+
+    /// Get the HTTP client used for all requests
+    pub fn client(&self) -> &HttpClient {
+        &self.client
+    }
+
+    /// Download several websites, sharing the client and the concurrency limits
+    /// 
+    /// Up to `parallel_sites` websites are processed at the same time. A
+    /// website that fails does not stop the others; its error is recorded in
+    /// the [`BatchResult`]. With progress output enabled, a line is printed
+    /// for every website as it finishes instead of per-file progress.
+    /// 
+    /// # Arguments
+    /// * `urls` - The base URLs of the websites
+    /// * `parallel_sites` - How many websites to process at the same time (at least 1)
+    /// 
+    /// # Returns
+    /// * `BatchResult` - The outcome of every website, in the order given
+    pub async fn download_all(&self, urls: &[String], parallel_sites: usize) -> BatchResult {
+        let sites = urls.iter().map(|url| (url.clone(), self.clone())).collect();
+        Self::download_sites(sites, parallel_sites).await
+    }
+
+    /// Download several websites, each with its own downloader
+    /// 
+    /// Like [`Downloader::download_all`], for websites that need different
+    /// settings, e.g. section filters or output directories. Build the
+    /// downloaders from clones of one [`DownloaderBuilder`] to share the
+    /// client and the [total concurrency limit](DownloaderBuilder::total_concurrent).
+    /// 
+    /// # Arguments
+    /// * `sites` - The base URLs of the websites and the downloader for each
+    /// * `parallel_sites` - How many websites to process at the same time (at least 1)
+    /// 
+    /// # Returns
+    /// * `BatchResult` - The outcome of every website, in the order given
+    pub async fn download_sites(sites: Vec<(String, Downloader)>, parallel_sites: usize) -> BatchResult {
+        let mut batch = BatchResult::new();
+        
+        let mut results = stream::iter(sites)
+            .map(|(url, downloader)| async move {
+                let quiet = Downloader {
+                    show_progress: false,
+                    ..downloader.clone()
+                };
+                let outcome = quiet.download(&url).await;
+                (downloader, SiteResult { url, outcome })
+            })
+            .buffered(parallel_sites.max(1));
+        
+        while let Some((downloader, site)) = results.next().await {
+            match &site.outcome {
+                Ok(result) => downloader.status(format!(
+                    "{} {}: {} downloaded, {} unchanged, {} failed",
+                    if result.failed.is_empty() { "✅" } else { "⚠️ " },
+                    site.domain(),
+                    result.success_count(),
+                    result.unchanged_count(),
+                    result.failure_count()
+                )),
+                Err(e) => downloader.status(format!("❌ {}: {e}", site.domain())),
+            }
+            batch.sites.push(site);
+        }
+        
+        batch
+    }
+
+    /// Download the files listed in a website's llms.txt (or llms-full.txt)
+    /// 
+    /// # Arguments
+    /// * `url` - The base URL of the website, e.g. `https://docs.example.com`
+    /// 
+    /// # Returns
+    /// * `Result<DownloadResult, DownloadError>` - Per-file outcomes, or an error if
+    ///   the index file could not be fetched or parsed
+    pub async fn download(&self, url: &str) -> Result<DownloadResult, DownloadError> {
+        let base_url = &validate_url(url)?;
+        // Discovery needs the trailing slash the validated URL drops, it marks a directory
+        let url = url.trim();
+        let client = &self.client;
+        let filter = &self.filter;
+        let source = self.source;
+        let max_concurrent = self.max_concurrent;
+        let mut result = DownloadResult::new();
+        
+        // A URL pointing at llms-full.txt asks for that file
+        let source = match IndexFile::of_url(base_url) {
+            Some(IndexFile::LlmsFullTxt) if source == SourceMode::LlmsTxt => SourceMode::Full,
+            _ => source,
+        };
+        
+        // Step 0: Check for llms-full.txt when requested
+        if source != SourceMode::LlmsTxt {
+            self.status("🔍 Looking for llms-full.txt...");
+            
+            // Only fetch the content up front when splitting, otherwise it is streamed to disk
+            let error = match discover(client, url, IndexFile::LlmsFullTxt, self.split).await {
+                Ok(found) => {
+                    self.report_discovery(IndexFile::LlmsFullTxt, &found);
+                    let saved = match &found.content {
+                        Some(content) => self.split_llms_full(base_url, &found.url, content).await,
+                        None => self.save_llms_full(base_url, &found.url).await,
+                    };
+                    match saved {
+                        Ok(result) => return Ok(result),
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => e,
+            };
+            
+            if source == SourceMode::PreferFull {
+                self.status(format!("⚠️  llms-full.txt is not available ({error})"));
+                self.status("↩️  Falling back to llms.txt");
+            } else {
+                return Err(error);
+            }
+        }
+        
+        // Step 1: Find the llms.txt file
+        self.status("🔍 Looking for llms.txt...");
+        let found = discover(client, url, IndexFile::LlmsTxt, true).await?;
+        self.report_discovery(IndexFile::LlmsTxt, &found);
+        let llms_txt_url = found.url;
+        let llms_content = found.content.unwrap_or_default();
+        
+        // Step 2: Parse llms.txt content into a structured document
+        self.status("📝 Parsing llms.txt content...");
+        // Relative links are relative to llms.txt, which may live in a subdirectory
+        let mut document = parse_llms_txt(&llms_content, &llms_txt_url)?;
+        
+        if let Some(title) = &document.title {
+            self.status(format!("📘 {title}"));
+        }
+        if let Some(summary) = &document.summary {
+            self.status(format!("   {summary}"));
+        }
+        
+        if document.is_empty() {
+            self.status("⚠️  No files found in llms.txt");
+            return Ok(result);
+        }
+        
+        // Apply section filters before any download task is spawned
+        let mut filtered = HashSet::new();
+        if !filter.is_empty() {
+            // Filtered entries are still listed, their files from earlier runs are kept
+            filtered = document
+                .sections
+                .iter()
+                .filter(|section| !filter.allows(section.name.as_deref()))
+                .flat_map(|section| section.entries.iter().map(|entry| entry.url.clone()))
+                .collect();
+            let skipped = document.apply_filter(filter);
+            self.status(format!("🔎 Section filters skipped {skipped} file(s)"));
+            
+            if document.is_empty() {
+                self.status("⚠️  No files left to download after applying section filters");
+                return Ok(result);
+            }
+        }
+        
+        let entries: Vec<(Option<String>, LlmsTxtEntry)> = document
+            .sections
+            .iter()
+            .flat_map(|section| section.entries.iter().map(|entry| (section.name.clone(), entry.clone())))
+            .collect();
+        
+        self.status(format!("📋 Found {} files to download", entries.len()));
+        for section in &document.sections {
+            if let Some(name) = &section.name {
+                self.status(format!("   • {name}: {} file(s)", section.entries.len()));
+            }
+        }
+        
+        // Step 3: Create local directory structure
+        let base_dir = self.domain_directory(base_url)?;
+        
+        // Load validators recorded by previous runs
+        let mut cache = DownloadCache::load(&base_dir);
+        let mut manifest = Manifest::new(&llms_txt_url, sha256_hex(llms_content.as_bytes()));
+        
+        // Map every entry to a safe local path before anything is downloaded
+        let previous_mapping = MappingManifest::load(&base_dir);
+        let mut mapper = PathMapper::new(&base_dir, base_url, self.cross_origin)?.with_previous(&previous_mapping);
+        let mut planned = Vec::with_capacity(entries.len());
+        let mut planned_paths = HashSet::new();
+        for (section, entry) in entries {
+            if self.respect_robots && !client.allowed_by_robots(&entry.url).await {
+                self.status(format!("🤖 Skipping {}: disallowed by robots.txt", entry.url));
+                self.emit(skipped_event(&entry.url, ROBOTS_SKIP_REASON));
+                result.add_skipped(entry.url, ROBOTS_SKIP_REASON);
+                continue;
+            }
+            
+            let cached_type = cache.get(&entry.url).and_then(|metadata| metadata.content_type.as_deref());
+            match map_entry(&mut mapper, &entry.url, cached_type) {
+                // Entries listed more than once are only downloaded once
+                Ok(local_path) if !planned_paths.insert(local_path.clone()) => {}
+                Ok(local_path) => planned.push((section, entry, local_path)),
+                Err(e) => {
+                    self.status(format!("🛡️  Skipping {}: {e}", entry.url));
+                    self.emit(finish_event(&entry.url, Err(&e)));
+                    result.add_failure(entry.url, e);
+                }
+            }
+        }
+        
+        if self.dry_run {
+            let planned = planned
+                .into_iter()
+                .map(|(section, entry, local_path)| PlannedFile {
+                    url: entry.url,
+                    path: local_path.display().to_string(),
+                    section,
+                    title: entry.title,
+                })
+                .collect();
+            return Ok(self.preview(&base_dir, planned, &filtered, result));
+        }
+        let mapper = Arc::new(Mutex::new(mapper));
+        
+        // Step 4: Download files concurrently with enhanced progress reporting
+        self.status(format!("\n🚀 Starting concurrent downloads with {max_concurrent} threads..."));
+        
+        // Create semaphore to limit concurrent downloads
+        let semaphore = Arc::new(Semaphore::new(max_concurrent));
+        
+        // Create multi-progress for concurrent downloads
+        let multi_progress = Arc::new(self.multi_progress());
+        
+        // Create overall progress bar
+        let overall_progress = multi_progress.add(ProgressBar::new(planned.len() as u64));
+        overall_progress.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) {msg}")
+                .unwrap()
+                .progress_chars("#>-")
+        );
+        overall_progress.set_message("Downloading files...");
+        
+        // Create download tasks for concurrent execution
+        let total_files = planned.len();
+        let download_tasks: Vec<_> = planned
+            .into_iter()
+            .enumerate()
+            .map(|(index, (_section, entry, local_path))| {
+                let client = client.clone();
+                let events = self.events.clone();
+                let mapper = Arc::clone(&mapper);
+                let multi_progress = Arc::clone(&multi_progress);
+                let overall_progress = overall_progress.clone();
+                let semaphore = Arc::clone(&semaphore);
+                let total_slots = self.total_slots.clone();
+                let cached = if self.use_cache { cache.get(&entry.url).cloned() } else { None };
+                
+                // The URL is kept next to the task, so a task that panics is still reported
+                let url = entry.url.clone();
+                let task = tokio::spawn(async move {
+                    // Acquire semaphore permit to limit concurrency
+                    let _permit = semaphore.acquire().await.unwrap();
+                    let _total_permit = match &total_slots {
+                        Some(total_slots) => Some(total_slots.acquire().await.unwrap()),
+                        None => None,
+                    };
+                    
+                    let file_url = entry.url;
+                    let filename = entry.title.unwrap_or_else(|| {
+                        file_url.split('/').next_back().unwrap_or(&file_url).to_string()
+                    });
+                    
+                    // Create individual progress bar for this download
+                    let spinner_style = ProgressStyle::default_spinner()
+                        .template("{spinner:.blue} [{elapsed_precise}] {msg}")
+                        .unwrap();
+                    let file_progress = multi_progress.add(ProgressBar::new_spinner());
+                    file_progress.set_style(spinner_style.clone());
+                    file_progress.set_message(format!("[{}/{}] {}", index + 1, total_files, filename));
+                    
+                    let emit = |event: DownloadEvent| {
+                        if let Some(events) = &events {
+                            events.emit(&event);
+                        }
+                    };
+                    emit(DownloadEvent::Start {
+                        url: file_url.clone(),
+                        path: local_path.display().to_string(),
+                        index: index + 1,
+                        total: total_files,
+                    });
+                    
+                    let result = download_single_file_with_progress(
+                        &client,
+                        &file_url,
+                        local_path,
+                        &mapper,
+                        cached.as_ref(),
+                        &file_progress,
+                    )
+                    .await;
+                    
+                    // The client switches to byte progress while streaming, go back to a plain line
+                    file_progress.set_style(spinner_style);
+                    match &result {
+                        Ok((_local_path, DownloadOutcome::Downloaded { metadata, resumed_bytes, .. })) => {
+                            let size_str = crate::error::DownloadResult::format_bytes(metadata.size);
+                            if *resumed_bytes > 0 {
+                                let resumed_str = crate::error::DownloadResult::format_bytes(*resumed_bytes);
+                                file_progress.finish_with_message(format!("✅ {filename} - {size_str} (resumed {resumed_str})"));
+                            } else {
+                                file_progress.finish_with_message(format!("✅ {filename} - {size_str}"));
+                            }
+                        }
+                        Ok((_local_path, DownloadOutcome::NotModified)) => {
+                            file_progress.finish_with_message(format!("♻️  {filename} - unchanged"));
+                        }
+                        Err(e) => {
+                            file_progress.finish_with_message(format!("❌ {filename} - {e}"));
+                        }
+                    }
+                    
+                    emit(finish_event(&file_url, result.as_ref().map(|(path, outcome)| (path.as_path(), outcome))));
+                    overall_progress.inc(1);
+                    (SystemTime::now(), result)
+                });
+                (url, task)
+            })
+            .collect();
+        
+        // Wait for all downloads to complete
+        let (urls, tasks): (Vec<_>, Vec<_>) = download_tasks.into_iter().unzip();
+        let download_results = join_all(tasks).await;
+        
+        // Process results
+        for (file_url, task_result) in urls.into_iter().zip(download_results) {
+            match task_result {
+                Ok((fetched_at, download_result)) => {
+                    match download_result {
+                        Ok((local_path, DownloadOutcome::Downloaded { metadata, resumed_bytes, status })) => {
+                            let bytes = metadata.size - resumed_bytes;
+                            manifest.add_file(&base_dir, &file_url, &local_path, &metadata, Some(status), fetched_at);
+                            cache.insert(file_url.clone(), metadata);
+                            result.add_success(file_url, local_path.display().to_string(), bytes);
+                            result.add_resumed(resumed_bytes);
+                        }
+                        Ok((local_path, DownloadOutcome::NotModified)) => {
+                            if let Some(metadata) = cache.get(&file_url) {
+                                manifest.add_file(&base_dir, &file_url, &local_path, metadata, Some(304), fetched_at);
+                            }
+                            result.add_unchanged(file_url, local_path.display().to_string());
+                        }
+                        Err(e) => {
+                            result.add_failure(file_url, e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    let error = DownloadError::TaskFailed(e.to_string());
+                    if let Some(events) = &self.events {
+                        events.emit(&finish_event(&file_url, Err(&error)));
+                    }
+                    overall_progress.inc(1);
+                    result.add_failure(file_url, error);
+                }
+            }
+        }
+        
+        overall_progress.finish_with_message("All downloads completed");
+        self.status(""); // Add spacing after progress bars
+        
+        // Remember validators so the next run can skip unchanged files
+        if let Err(e) = cache.save() {
+            self.status(format!("⚠️  Could not save download cache: {e}"));
+        }
+        self.save_manifests(&base_dir, manifest, &filtered, &mut result);
+        
+        Ok(result)
+    }
+
+    /// Streams llms-full.txt to the domain directory without holding it in memory
+    async fn save_llms_full(&self, base_url: &str, full_url: &str) -> Result<DownloadResult, DownloadError> {
+        let mut result = DownloadResult::new();
+        
+        let base_dir = self.domain_directory(base_url)?;
+        
+        let mut mapper = PathMapper::new(&base_dir, base_url, CrossOriginPolicy::Reject)?;
+        let local_path = mapper.map(full_url)?;
+        if self.dry_run {
+            let planned = PlannedFile {
+                url: full_url.to_string(),
+                path: local_path.display().to_string(),
+                section: None,
+                title: None,
+            };
+            return Ok(self.preview(&base_dir, vec![planned], &HashSet::new(), result));
+        }
+        let mapper = Mutex::new(mapper);
+        
+        let mut cache = DownloadCache::load(&base_dir);
+        let cached = if self.use_cache { cache.get(full_url).cloned() } else { None };
+        
+        let multi_progress = self.multi_progress();
+        let progress = multi_progress.add(ProgressBar::new_spinner());
+        progress.set_message("llms-full.txt");
+        
+        self.emit(DownloadEvent::Start {
+            url: full_url.to_string(),
+            path: local_path.display().to_string(),
+            index: 1,
+            total: 1,
+        });
+        let outcome = download_single_file_with_progress(
+            &self.client,
+            full_url,
+            local_path,
+            &mapper,
+            cached.as_ref(),
+            &progress,
+        )
+        .await;
+        progress.finish_and_clear();
+        self.emit(finish_event(full_url, outcome.as_ref().map(|(path, outcome)| (path.as_path(), outcome))));
+        
+        let fetched_at = SystemTime::now();
+        let mut manifest = None;
+        match outcome? {
+            (local_path, DownloadOutcome::Downloaded { metadata, resumed_bytes, status }) => {
+                self.status(format!(
+                    "✅ Saved llms-full.txt ({}) to: {}",
+                    DownloadResult::format_bytes(metadata.size),
+                    local_path.display()
+                ));
+                result.add_success(
+                    full_url.to_string(),
+                    local_path.display().to_string(),
+                    metadata.size - resumed_bytes,
+                );
+                result.add_resumed(resumed_bytes);
+                let mut full_manifest = Manifest::new(full_url, metadata.sha256.clone());
+                full_manifest.add_file(&base_dir, full_url, &local_path, &metadata, Some(status), fetched_at);
+                manifest = Some(full_manifest);
+                cache.insert(full_url.to_string(), metadata);
+            }
+            (local_path, DownloadOutcome::NotModified) => {
+                self.status(format!("♻️  llms-full.txt is unchanged: {}", local_path.display()));
+                result.add_unchanged(full_url.to_string(), local_path.display().to_string());
+                if let Some(metadata) = cache.get(full_url) {
+                    let mut full_manifest = Manifest::new(full_url, metadata.sha256.clone());
+                    full_manifest.add_file(&base_dir, full_url, &local_path, metadata, Some(304), fetched_at);
+                    manifest = Some(full_manifest);
+                }
+            }
+        }
+        
+        if let Err(e) = cache.save() {
+            self.status(format!("⚠️  Could not save download cache: {e}"));
+        }
+        match manifest {
+            Some(manifest) => self.save_manifests(&base_dir, manifest, &HashSet::new(), &mut result),
+            None => self.save_mapping(&base_dir, &result),
+        }
+        
+        Ok(result)
+    }
+
+    /// Splits llms-full.txt back into one file per document
+    /// 
+    /// llms.txt is consulted (if available) to map each document to the URL
+    /// it is listed under, so the resulting layout matches a regular llms.txt
+    /// download. Documents not listed there fall back to their `Source:` URL,
+    /// then to a file named after their title.
+    async fn split_llms_full(&self, base_url: &str, full_url: &str, content: &str) -> Result<DownloadResult, DownloadError> {
+        let mut result = DownloadResult::new();
+        
+        let base_dir = self.domain_directory(base_url)?;
+        
+        // Use the llms.txt next to llms-full.txt, when available, to map documents back to their original URLs
+        let index = match discover(&self.client, full_url, IndexFile::LlmsTxt, true).await {
+            Ok(Discovered { url, content: Some(index_content), .. }) => parse_llms_txt(&index_content, &url).ok(),
+            _ => None,
+        };
+        if index.is_none() {
+            self.status("⚠️  No llms.txt available, naming documents after their source or title");
+        }
+        
+        let mut manifest = Manifest::new(full_url, sha256_hex(content.as_bytes()));
+        let previous_mapping = MappingManifest::load(&base_dir);
+        let mut mapper = PathMapper::new(&base_dir, base_url, self.cross_origin)?.with_previous(&previous_mapping);
+        let mut planned = Vec::new();
+        let mut filtered = HashSet::new();
+        let documents = parser::split_llms_full(content, full_url)?;
+        let full = Url::parse(full_url)?;
+        self.status(format!("✂️  Splitting llms-full.txt into {} documents...", documents.len()));
+        
+        for (position, document) in documents.iter().enumerate() {
+            let listed = document
+                .title
+                .as_deref()
+                .and_then(|title| index.as_ref()?.find_by_title(title));
+            
+            // Section filters apply to documents we can match to an llms.txt section
+            if let Some((section, entry)) = listed {
+                if !self.filter.allows(section.name.as_deref()) {
+                    filtered.insert(entry.url.clone());
+                    continue;
+                }
+            }
+            
+            let document_url = match (listed, &document.source_url) {
+                (Some((_, entry)), _) => entry.url.clone(),
+                (None, Some(source_url)) => with_markdown_extension(source_url),
+                (None, None) => {
+                    let stem = match &document.title {
+                        Some(title) => slugify(title),
+                        None => format!("document-{}", position + 1),
+                    };
+                    // Named documents are saved next to llms-full.txt
+                    full.join(&format!("{stem}.md"))?.to_string()
+                }
+            };
+            
+            if self.dry_run {
+                match mapper.map(&document_url) {
+                    Ok(local_path) => planned.push(PlannedFile {
+                        url: document_url,
+                        path: local_path.display().to_string(),
+                        section: listed.and_then(|(section, _)| section.name.clone()),
+                        title: document.title.clone(),
+                    }),
+                    Err(e) => result.add_failure(document_url, e),
+                }
+                continue;
+            }
+            
+            let write_result = async {
+                let local_path = mapper.map(&document_url)?;
+                if let Some(parent) = local_path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(&local_path, &document.content).await?;
+                Ok::<_, DownloadError>(local_path)
+            }
+            .await;
+            
+            match write_result {
+                Ok(local_path) => {
+                    let metadata = FileMetadata {
+                        etag: None,
+                        last_modified: None,
+                        size: document.content.len() as u64,
+                        sha256: sha256_hex(document.content.as_bytes()),
+                        content_type: None,
+                    };
+                    manifest.add_file(&base_dir, &document_url, &local_path, &metadata, None, SystemTime::now());
+                    self.emit(DownloadEvent::Finish {
+                        url: document_url.clone(),
+                        outcome: FinishOutcome::Downloaded,
+                        path: Some(local_path.display().to_string()),
+                        bytes: Some(metadata.size),
+                        error: None,
+                    });
+                    result.add_success(document_url, local_path.display().to_string(), metadata.size);
+                }
+                Err(e) => {
+                    self.emit(finish_event(&document_url, Err(&e)));
+                    result.add_failure(document_url, e);
+                }
+            }
+        }
+        if self.dry_run {
+            return Ok(self.preview(&base_dir, planned, &filtered, result));
+        }
+        self.save_manifests(&base_dir, manifest, &filtered, &mut result);
+        
+        Ok(result)
+    }
+
+    /// Reports where an index file was found and the candidates tried before
+    fn report_discovery(&self, file: IndexFile, found: &Discovered) {
+        for (url, kind) in &found.tried {
+            self.status(format!("   ✗ {url} ({kind})"));
+        }
+        self.status(format!("✅ Found {} at: {}", file.file_name(), found.url));
+    }
+
+    /// Gets the domain directory for a website, creating it unless this is a dry run
+    fn domain_directory(&self, base_url: &str) -> Result<PathBuf, DownloadError> {
+        if self.dry_run {
+            let base_dir = local_directory(base_url, &self.output_dir)?;
+            self.status(format!("📁 Files would be saved to: {}", base_dir.display()));
+            return Ok(base_dir);
+        }
+        
+        self.status("📁 Creating local directory structure...");
+        let base_dir = create_local_directory(base_url, &self.output_dir)?;
+        self.status(format!("📁 Files will be saved to: {}", base_dir.display()));
+        Ok(base_dir)
+    }
+
+    /// Records the files a run would save and compares them with the previous
+    /// run, without downloading or writing anything
+    /// 
+    /// `filtered` lists the URLs dropped by section filters, which are kept
+    /// like those of failed or skipped files.
+    fn preview(
+        &self,
+        base_dir: &Path,
+        planned: Vec<PlannedFile>,
+        filtered: &HashSet<String>,
+        mut result: DownloadResult,
+    ) -> DownloadResult {
+        let previous = Manifest::load(base_dir);
+        let relative_paths: Vec<(&str, String)> = planned
+            .iter()
+            .filter_map(|file| Some((file.url.as_str(), relative_path(base_dir, Path::new(&file.path))?)))
+            .collect();
+        
+        let mut changes = Changes::compare(
+            previous.as_ref(),
+            &MappingManifest::load(base_dir),
+            relative_paths.iter().map(|(url, path)| (*url, path.as_str(), None)),
+            &retained_urls(&result, filtered),
+        );
+        changes.prune = self.prune;
+        changes.dry_run = true;
+        result.changes = Some(changes);
+        result.planned = planned;
+        result
+    }
+
+    /// Writes the download manifest and the mapping manifest for a run
+    /// 
+    /// The new manifest is compared with the previous run's first, and files
+    /// that are no longer listed are pruned according to the prune mode.
+    /// Files of the URLs in `filtered`, dropped by section filters, are kept.
+    fn save_manifests(
+        &self,
+        base_dir: &Path,
+        mut manifest: Manifest,
+        filtered: &HashSet<String>,
+        result: &mut DownloadResult,
+    ) {
+        let previous = Manifest::load(base_dir);
+        let mut changes = Changes::compare(
+            previous.as_ref(),
+            &MappingManifest::load(base_dir),
+            manifest
+                .files
+                .iter()
+                .map(|file| (file.url.as_str(), file.path.as_str(), Some(file.sha256.as_str()))),
+            &retained_urls(result, filtered),
+        );
+        changes.prune = self.prune;
+        self.prune_removed(base_dir, &manifest, &changes, result);
+        result.changes = Some(changes);
+        
+        manifest.add_failures(result);
+        manifest.add_skipped(result);
+        if let Err(e) = manifest.save(base_dir) {
+            self.status(format!("⚠️  Could not save manifest: {e}"));
+        }
+        self.save_mapping(base_dir, result);
+    }
+
+    /// Deletes or quarantines the files of the previous run that are no longer listed
+    /// 
+    /// Files that cannot be pruned are recorded as failures.
+    fn prune_removed(&self, base_dir: &Path, manifest: &Manifest, changes: &Changes, result: &mut DownloadResult) {
+        if self.prune == PruneMode::Keep || changes.removed.is_empty() {
+            return;
+        }
+        
+        let mut cache = DownloadCache::load(base_dir);
+        for file in &changes.removed {
+            match prune_file(base_dir, &file.path, self.prune) {
+                // A URL that is still listed under another path keeps its validators
+                Ok(()) if !manifest.files.iter().any(|listed| listed.url == file.url) => cache.remove(&file.url),
+                Ok(()) => {}
+                Err(e) => {
+                    self.status(format!("⚠️  Could not prune {}: {e}", file.path));
+                    result.add_failure(file.url.clone(), e);
+                }
+            }
+        }
+        if let Err(e) = cache.save() {
+            self.status(format!("⚠️  Could not save download cache: {e}"));
+        }
+    }
+
+    /// Records the original URL of every local file in the mapping manifest
+    fn save_mapping(&self, base_dir: &Path, result: &DownloadResult) {
+        let mut mapping = MappingManifest::load(base_dir);
+        for (url, local_path) in result.successful.iter().chain(&result.unchanged) {
+            mapping.insert(base_dir, Path::new(local_path), url);
+        }
+        
+        if let Err(e) = mapping.save(base_dir) {
+            self.status(format!("⚠️  Could not save mapping manifest: {e}"));
+        }
+    }
+
+    /// Send an event to the event sink, if there is one
+    fn emit(&self, event: DownloadEvent) {
+        if let Some(events) = &self.events {
+            events.emit(&event);
+        }
+    }
+
+    /// Print a status message when progress output is enabled
+    fn status(&self, message: impl fmt::Display) {
+        if self.show_progress {
+            println!("{message}");
+        }
+    }
+
+    /// Create the container for per-file progress bars
+    fn multi_progress(&self) -> MultiProgress {
+        if self.show_progress {
+            MultiProgress::new()
+        } else {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        }
+    }
+}
+
+/// Appends `.md` to URLs whose last path segment has no extension
+fn with_markdown_extension(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            let last_segment = parsed.path().rsplit('/').next().unwrap_or_default();
+            if !last_segment.is_empty() && !last_segment.contains('.') {
+                let path = format!("{}.md", parsed.path());
+                parsed.set_path(&path);
+            }
+            parsed.to_string()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+/// Gets the URLs of a run that are still listed but are not up to date
+/// locally, so their files from the previous run are kept
+/// 
+/// These are the failed, skipped and unchanged files of the run and the
+/// entries dropped by section filters.
+fn retained_urls<'a>(result: &'a DownloadResult, filtered: &'a HashSet<String>) -> HashSet<&'a str> {
+    result
+        .failed
+        .iter()
+        .map(|failure| failure.url.as_str())
+        .chain(result.skipped.iter().map(|skipped| skipped.url.as_str()))
+        .chain(result.unchanged.iter().map(|(url, _)| url.as_str()))
+        .chain(filtered.iter().map(String::as_str))
+        .collect()
+}
+
+/// Builds the finish event for a file that was skipped by policy
+fn skipped_event(url: &str, reason: &str) -> DownloadEvent {
+    DownloadEvent::Finish {
+        url: url.to_string(),
+        outcome: FinishOutcome::Skipped,
+        path: None,
+        bytes: None,
+        error: Some(reason.to_string()),
+    }
+}
+
+/// Builds the event reporting how the download of a file ended
+fn finish_event(url: &str, outcome: Result<(&Path, &DownloadOutcome), &DownloadError>) -> DownloadEvent {
+    let (outcome, path, bytes, error) = match outcome {
+        Ok((path, DownloadOutcome::Downloaded { metadata, resumed_bytes, .. })) => {
+            (FinishOutcome::Downloaded, Some(path), Some(metadata.size - resumed_bytes), None)
+        }
+        Ok((path, DownloadOutcome::NotModified)) => (FinishOutcome::Unchanged, Some(path), None, None),
+        Err(e) => (FinishOutcome::Failed, None, None, Some(e.to_string())),
+    };
+    
+    DownloadEvent::Finish {
+        url: url.to_string(),
+        outcome,
+        path: path.map(|path| path.display().to_string()),
+        bytes,
+        error,
+    }
+}
+
+/// Maps an entry to its local path, using the extension of the content type
+/// recorded by a previous run when the URL has none
+fn map_entry(mapper: &mut PathMapper, url: &str, cached_type: Option<&str>) -> Result<PathBuf, DownloadError> {
+    let local_path = mapper.map(url)?;
+    match cached_type.and_then(extension_for_content_type) {
+        Some(extension) if local_path.extension().is_none() => mapper.with_extension(&local_path, extension),
+        _ => Ok(local_path),
+    }
+}
+
+/// Downloads a single file with progress reporting for concurrent downloads
+/// Returns the local path and whether the file was downloaded or unchanged
+/// 
+/// Files saved without an extension are renamed once the response's
+/// Content-Type tells which extension they should have.
+async fn download_single_file_with_progress(
+    client: &HttpClient,
+    file_url: &str,
+    local_path: PathBuf,
+    mapper: &Mutex<PathMapper>,
+    cached: Option<&FileMetadata>,
+    progress: &ProgressBar,
+) -> Result<(PathBuf, DownloadOutcome), DownloadError> {
+    // Only revalidate if the local copy still matches what was recorded
+    let cached = cached.filter(|metadata| metadata.has_validators() && metadata.matches_file(&local_path));
+    
+    // Update progress to show we're starting
+    progress.set_message(format!("Starting download: {}", 
+        file_url.split('/').next_back().unwrap_or(file_url)));
+    
+    // Download the file unless the server reports it unchanged
+    let outcome = client
+        .download_file_if_modified(file_url, &local_path, cached, Some(progress))
+        .await?;
+    
+    let extension = match &outcome {
+        DownloadOutcome::Downloaded { metadata, .. } if local_path.extension().is_none() => {
+            metadata.content_type.as_deref().and_then(extension_for_content_type)
+        }
+        _ => None,
+    };
+    if let Some(extension) = extension {
+        let extended = mapper.lock().unwrap().with_extension(&local_path, extension)?;
+        tokio::fs::rename(&local_path, &extended).await?;
+        return Ok((extended, outcome));
+    }
+    
+    Ok((local_path, outcome))
+}
+
+/// Validates the provided URL and ensures it's properly formatted
+/// 
+/// Only http and https URLs with a host are accepted. The returned URL has
+/// no trailing slash.
+pub fn validate_url(url_str: &str) -> Result<String, DownloadError> {
+    // Parse the URL to validate its format
+    let parsed_url = Url::parse(url_str)
+        .map_err(|e| DownloadError::InvalidUrl(format!("Invalid URL format: {e}")))?;
+    
+    // Ensure the URL has a valid scheme (http or https)
+    match parsed_url.scheme() {
+        "http" | "https" => {},
+        scheme => return Err(DownloadError::InvalidUrl(
+            format!("Unsupported URL scheme '{scheme}'. Only http and https are supported.")
+        )),
+    }
+    
+    // Ensure the URL has a host
+    if parsed_url.host().is_none() {
+        return Err(DownloadError::InvalidUrl(
+            "URL must have a valid host".to_string()
+        ));
+    }
+    
+    // Return the URL without trailing slash for consistency
+    let mut clean_url = parsed_url.to_string();
+    if clean_url.ends_with('/') {
+        clean_url.pop();
+    }
+    
+    Ok(clean_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_manager::local_directory;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    
+    const LLMS_TXT: &str = "# Site\n\n## Docs\n\n- [A](/a.md)\n\n## Optional\n\n- [B](/b.md)\n";
+    const VERSIONED_LLMS_TXT: &str = "# Site v1.2\n\n## Docs\n\n- [C](/c.md)\n";
+
+    /// Serve the llms.txt above and its documents until the test ends
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 4096];
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]);
+                    let mut request_line = request.split_whitespace();
+                    let method = request_line.next().unwrap_or_default();
+                    let body = match request_line.next().unwrap_or_default() {
+                        "/llms.txt" => Some(LLMS_TXT),
+                        "/docs/v1.2/llms.txt" => Some(VERSIONED_LLMS_TXT),
+                        "/a.md" => Some("alpha"),
+                        "/b.md" => Some("beta"),
+                        "/c.md" => Some("gamma"),
+                        _ => None,
+                    };
+                    let (status, body) = match body {
+                        Some(body) => ("200 OK", body),
+                        None => ("404 Not Found", ""),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {status}\r\nContent-Type: text/markdown\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        if method == "HEAD" { "" } else { body },
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{address}")
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("llmsdl-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn filtered_run_with_prune_keeps_files() {
+        let url = serve().await;
+        let output_dir = temp_dir("filtered-prune");
+        
+        let result = Downloader::builder().output_dir(&output_dir).build().download(&url).await.unwrap();
+        assert!(result.failed.is_empty());
+        let domain_dir = local_directory(&url, &output_dir).unwrap();
+        assert!(domain_dir.join("b.md").is_file());
+        
+        let filter = SectionFilter {
+            skip_optional: true,
+            ..SectionFilter::default()
+        };
+        let result = Downloader::builder()
+            .output_dir(&output_dir)
+            .filter(filter)
+            .prune(PruneMode::Delete)
+            .build()
+            .download(&url)
+            .await
+            .unwrap();
+        
+        assert!(result.changes.unwrap().removed.is_empty());
+        assert!(domain_dir.join("a.md").is_file());
+        assert!(domain_dir.join("b.md").is_file());
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+    
+    #[tokio::test]
+    async fn trailing_slash_marks_a_dotted_directory() {
+        let url = serve().await;
+        let output_dir = temp_dir("dotted-directory");
+        
+        let result = Downloader::builder()
+            .output_dir(&output_dir)
+            .build()
+            .download(&format!("{url}/docs/v1.2/"))
+            .await
+            .unwrap();
+        
+        // The llms.txt of /docs/v1.2/ lists c.md, the one at the root does not
+        assert!(result.failed.is_empty());
+        assert!(result.successful.iter().any(|(file_url, _)| file_url.ends_with("/c.md")));
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::sync::Changes;
 
 /// Type alias for Results using our custom DownloadError
 pub type Result<T> = std::result::Result<T, DownloadError>;
@@ -16,24 +18,48 @@ pub enum DownloadError {
     /// Content parsing errors
     ParseError(String),
     /// HTTP status errors with specific status codes
-    HttpError { status: u16, url: String },
+    HttpError {
+        /// HTTP status code returned by the server
+        status: u16,
+        /// URL of the failed request
+        url: String,
+    },
     /// File not found (404) errors
     FileNotFound(String),
 
     /// Timeout errors
     Timeout(String),
+    /// URLs that cannot be mapped to a safe path inside the output directory
+    UnsafePath(String),
+    /// Invalid settings, e.g. a malformed header
+    InvalidConfig(String),
+    /// A download task panicked or was cancelled
+    TaskFailed(String),
+    /// The website's llms.txt or llms-full.txt could not be fetched
+    LlmsTxtUnavailable {
+        /// URL of the llms.txt or llms-full.txt file
+        url: String,
+        /// Why it could not be fetched, included in the error message
+        source: Box<DownloadError>,
+        /// Every location that was tried, in order
+        tried: Vec<String>,
+    },
 }
 
 impl fmt::Display for DownloadError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DownloadError::NetworkError(err) => {
-                if err.is_timeout() {
+                if network_error_kind(err) == ErrorKind::Tls {
+                    write!(f, "TLS error: The secure connection could not be established ({}).", root_cause(err))
+                } else if err.is_timeout() {
                     write!(f, "Network timeout: The request took too long to complete. Try again later or check your internet connection.")
                 } else if err.is_connect() {
                     write!(f, "Connection failed: Unable to connect to the server. Please check your internet connection and verify the server is accessible.")
                 } else if err.is_request() {
                     write!(f, "Request error: Invalid request format or parameters. The URL may be malformed.")
+                } else if err.is_redirect() {
+                    write!(f, "Redirect error: {}.", root_cause(err))
                 } else if err.is_decode() {
                     write!(f, "Content decode error: The server response could not be decoded properly.")
                 } else {
@@ -67,14 +93,240 @@ impl fmt::Display for DownloadError {
                     404 => write!(f, "File not found (404): The file at {url} does not exist on the server."),
                     403 => write!(f, "Access forbidden (403): You don't have permission to access {url}."),
                     401 => write!(f, "Unauthorized (401): Authentication required to access {url}."),
+                    429 => write!(f, "Too many requests (429): The server is rate limiting requests to {url}."),
                     500..=599 => write!(f, "Server error ({status}): The server encountered an error while processing {url}."),
                     _ => write!(f, "HTTP error ({status}): Request to {url} failed."),
                 }
             },
             DownloadError::FileNotFound(url) => write!(f, "File not found: {url} is not available on the server."),
             DownloadError::Timeout(url) => write!(f, "Timeout: Request to {url} took too long. The server may be overloaded."),
+            DownloadError::UnsafePath(msg) => write!(f, "Unsafe path: {msg}. The file was not saved."),
+            DownloadError::InvalidConfig(msg) => write!(f, "Invalid configuration: {msg}."),
+            DownloadError::TaskFailed(msg) => write!(f, "Download task failed: {msg}. This is a bug in llmsdl, please report it."),
+            DownloadError::LlmsTxtUnavailable { url, source, tried } => {
+                write!(f, "Could not find or access {url}: {source}")?;
+                if tried.len() > 1 {
+                    write!(f, " Tried {} locations: {}.", tried.len(), tried.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Category of a [`DownloadError`], used for statistics, troubleshooting
+/// hints and machine-readable reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// The server has no such file (404 / 410)
+    NotFound,
+    /// The server refused access (403)
+    Forbidden,
+    /// Authentication is required (401 / 407)
+    Auth,
+    /// The server is throttling requests (429)
+    RateLimited,
+    /// The server failed to handle the request (5xx)
+    Server,
+    /// Any other unsuccessful HTTP status
+    Http,
+    /// The request took too long
+    Timeout,
+    /// The TLS handshake or certificate validation failed
+    Tls,
+    /// The host name could not be resolved
+    Dns,
+    /// The server actively refused the connection
+    ConnectionRefused,
+    /// Any other network error
+    Network,
+    /// Writing to the output directory is not permitted
+    IoPermission,
+    /// The disk is full
+    DiskFull,
+    /// Any other file system error
+    Io,
+    /// The URL is malformed or unsupported
+    InvalidUrl,
+    /// Content could not be parsed
+    Parse,
+    /// The URL cannot be mapped to a safe local path
+    UnsafePath,
+    /// The settings are invalid
+    Config,
+    /// A download task ended unexpectedly
+    Internal,
+}
+
+impl ErrorKind {
+    /// Stable identifier of the kind, e.g. `not-found`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::NotFound => "not-found",
+            ErrorKind::Forbidden => "forbidden",
+            ErrorKind::Auth => "auth",
+            ErrorKind::RateLimited => "rate-limited",
+            ErrorKind::Server => "server",
+            ErrorKind::Http => "http",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Tls => "tls",
+            ErrorKind::Dns => "dns",
+            ErrorKind::ConnectionRefused => "connection-refused",
+            ErrorKind::Network => "network",
+            ErrorKind::IoPermission => "io-permission",
+            ErrorKind::DiskFull => "disk-full",
+            ErrorKind::Io => "io",
+            ErrorKind::InvalidUrl => "invalid-url",
+            ErrorKind::Parse => "parse",
+            ErrorKind::UnsafePath => "unsafe-path",
+            ErrorKind::Config => "config",
+            ErrorKind::Internal => "internal",
+        }
+    }
+
+    /// Human-readable description used in the error breakdown
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorKind::NotFound => "Files not found (404)",
+            ErrorKind::Forbidden => "Access forbidden (403)",
+            ErrorKind::Auth => "Authentication required (401)",
+            ErrorKind::RateLimited => "Rate limited (429)",
+            ErrorKind::Server => "Server errors (5xx)",
+            ErrorKind::Http => "Other HTTP errors",
+            ErrorKind::Timeout => "Network timeouts",
+            ErrorKind::Tls => "TLS/certificate errors",
+            ErrorKind::Dns => "DNS resolution failures",
+            ErrorKind::ConnectionRefused => "Connections refused",
+            ErrorKind::Network => "Network/connection errors",
+            ErrorKind::IoPermission => "Permission denied",
+            ErrorKind::DiskFull => "Disk full",
+            ErrorKind::Io => "File system errors",
+            ErrorKind::InvalidUrl => "Invalid URLs",
+            ErrorKind::Parse => "Parse errors",
+            ErrorKind::UnsafePath => "Unsafe local paths",
+            ErrorKind::Config => "Invalid configuration",
+            ErrorKind::Internal => "Internal errors",
+        }
+    }
+
+    /// Check if the kind is caused by the network rather than the server's answer
+    pub fn is_network(&self) -> bool {
+        matches!(
+            self,
+            ErrorKind::Timeout | ErrorKind::Tls | ErrorKind::Dns | ErrorKind::ConnectionRefused | ErrorKind::Network
+        )
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl DownloadError {
+    /// Get the category of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            DownloadError::NetworkError(err) => network_error_kind(err),
+            DownloadError::IoError(err) => io_error_kind(err),
+            DownloadError::InvalidUrl(_) => ErrorKind::InvalidUrl,
+            DownloadError::ParseError(_) => ErrorKind::Parse,
+            DownloadError::HttpError { status, .. } => status_kind(*status),
+            DownloadError::FileNotFound(_) => ErrorKind::NotFound,
+            DownloadError::Timeout(_) => ErrorKind::Timeout,
+            DownloadError::UnsafePath(_) => ErrorKind::UnsafePath,
+            DownloadError::InvalidConfig(_) => ErrorKind::Config,
+            DownloadError::TaskFailed(_) => ErrorKind::Internal,
+            DownloadError::LlmsTxtUnavailable { source, .. } => source.kind(),
+        }
+    }
+
+    /// Get the messages of the error and all of its sources, outermost first
+    pub fn chain(&self) -> Vec<String> {
+        let mut chain = vec![self.to_string()];
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            chain.push(error.to_string());
+            source = error.source();
+        }
+        chain
+    }
+}
+
+/// Categorize an HTTP status code
+fn status_kind(status: u16) -> ErrorKind {
+    match status {
+        404 | 410 => ErrorKind::NotFound,
+        403 => ErrorKind::Forbidden,
+        401 | 407 => ErrorKind::Auth,
+        429 => ErrorKind::RateLimited,
+        500..=599 => ErrorKind::Server,
+        _ => ErrorKind::Http,
+    }
+}
+
+/// Categorize a file system error
+fn io_error_kind(err: &std::io::Error) -> ErrorKind {
+    match err.kind() {
+        std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => ErrorKind::IoPermission,
+        std::io::ErrorKind::StorageFull => ErrorKind::DiskFull,
+        std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+        std::io::ErrorKind::ConnectionRefused => ErrorKind::ConnectionRefused,
+        std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::ConnectionAborted | std::io::ErrorKind::UnexpectedEof => {
+            ErrorKind::Network
+        }
+        _ => ErrorKind::Io,
+    }
+}
+
+/// Categorize a network error by walking its source chain
+/// 
+/// TLS failures surface as `native_tls::Error`, either directly or wrapped in
+/// an `io::Error`. Name resolution failures are the `io::Error` below a hyper
+/// connect error that carries no OS error code, unlike a refused or reset
+/// connection.
+fn network_error_kind(err: &reqwest::Error) -> ErrorKind {
+    if err.is_timeout() {
+        return ErrorKind::Timeout;
+    }
+    if let Some(status) = err.status() {
+        return status_kind(status.as_u16());
+    }
+    
+    let mut connecting = err.is_connect();
+    let mut source = std::error::Error::source(err);
+    while let Some(error) = source {
+        if error.downcast_ref::<native_tls::Error>().is_some() {
+            return ErrorKind::Tls;
+        }
+        if let Some(hyper_error) = error.downcast_ref::<hyper::Error>() {
+            connecting |= hyper_error.is_connect();
         }
+        if let Some(io_error) = error.downcast_ref::<std::io::Error>() {
+            if io_error.get_ref().is_some_and(|inner| inner.is::<native_tls::Error>()) {
+                return ErrorKind::Tls;
+            }
+            match io_error_kind(io_error) {
+                ErrorKind::Io if connecting && io_error.raw_os_error().is_none() => return ErrorKind::Dns,
+                ErrorKind::Io => {}
+                kind => return kind,
+            }
+        }
+        source = error.source();
+    }
+    
+    ErrorKind::Network
+}
+
+/// Get the message of the innermost source of an error
+fn root_cause(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut cause = err;
+    while let Some(source) = cause.source() {
+        cause = source;
     }
+    cause.to_string()
 }
 
 impl std::error::Error for DownloadError {
@@ -87,6 +339,11 @@ impl std::error::Error for DownloadError {
             DownloadError::HttpError { .. } => None,
             DownloadError::FileNotFound(_) => None,
             DownloadError::Timeout(_) => None,
+            DownloadError::UnsafePath(_) => None,
+            DownloadError::InvalidConfig(_) => None,
+            DownloadError::TaskFailed(_) => None,
+            // The message of the source is already part of this error's message
+            DownloadError::LlmsTxtUnavailable { .. } => None,
         }
     }
 }
@@ -110,17 +367,75 @@ impl From<url::ParseError> for DownloadError {
     }
 }
 
+/// A file that could not be downloaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadFailure {
+    /// URL of the file
+    pub url: String,
+    /// Category of the error
+    pub kind: ErrorKind,
+    /// Error message
+    pub error: String,
+    /// Messages of the errors that caused it, outermost first
+    pub sources: Vec<String>,
+}
+
+impl DownloadFailure {
+    /// Record why a file could not be downloaded
+    pub fn new(url: String, error: &DownloadError) -> Self {
+        let mut chain = error.chain().into_iter();
+        Self {
+            url,
+            kind: error.kind(),
+            error: chain.next().unwrap_or_default(),
+            sources: chain.collect(),
+        }
+    }
+}
+
+/// A file that was not downloaded because a policy forbids it, e.g. robots.txt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    /// URL of the file
+    pub url: String,
+    /// Why the file was skipped
+    pub reason: String,
+}
+
+/// A file a dry run would download, and where it would be saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedFile {
+    /// URL of the file
+    pub url: String,
+    /// Local path the file would be saved to
+    pub path: String,
+    /// Name of the llms.txt section the file is listed in
+    pub section: Option<String>,
+    /// Link text of the entry, or the title of a document split out of llms-full.txt
+    pub title: Option<String>,
+}
+
 /// Result type for tracking download operations
 #[derive(Debug, Clone)]
 pub struct DownloadResult {
     /// Successfully downloaded files with their local paths
     pub successful: Vec<(String, String)>, // (URL, local_path)
-    /// Failed downloads with error messages and error types
-    pub failed: Vec<(String, String, String)>, // (URL, error_message, error_type)
+    /// Files skipped because the server reported them as not modified
+    pub unchanged: Vec<(String, String)>, // (URL, local_path)
+    /// Failed downloads
+    pub failed: Vec<DownloadFailure>,
+    /// Files skipped by policy, not counted in `total_files`
+    pub skipped: Vec<SkippedFile>,
     /// Total number of files processed
     pub total_files: usize,
     /// Total bytes downloaded
     pub total_bytes: u64,
+    /// Bytes reused from partial files instead of being downloaded again
+    pub resumed_bytes: u64,
+    /// What changed since the previous run, once the manifest was compared
+    pub changes: Option<Changes>,
+    /// Files a dry run would download, in the order they are listed
+    pub planned: Vec<PlannedFile>,
     /// Start time for duration calculation
     pub start_time: std::time::Instant,
 }
@@ -130,9 +445,14 @@ impl DownloadResult {
     pub fn new() -> Self {
         Self {
             successful: Vec::new(),
+            unchanged: Vec::new(),
             failed: Vec::new(),
+            skipped: Vec::new(),
             total_files: 0,
             total_bytes: 0,
+            resumed_bytes: 0,
+            changes: None,
+            planned: Vec::new(),
             start_time: std::time::Instant::now(),
         }
     }
@@ -144,18 +464,48 @@ impl DownloadResult {
         self.total_bytes += bytes;
     }
 
+    /// Record bytes that were reused from a partial download
+    pub fn add_resumed(&mut self, bytes: u64) {
+        self.resumed_bytes += bytes;
+    }
+
+    /// Add a file that was already up to date
+    pub fn add_unchanged(&mut self, url: String, local_path: String) {
+        self.unchanged.push((url, local_path));
+        self.total_files += 1;
+    }
+
     /// Add a failed download
-    pub fn add_failure(&mut self, url: String, error: String) {
-        let error_type = self.categorize_error(&error);
-        self.failed.push((url, error, error_type));
+    pub fn add_failure(&mut self, url: String, error: DownloadError) {
+        self.failed.push(DownloadFailure::new(url, &error));
         self.total_files += 1;
     }
 
+    /// Add a file that was skipped by policy
+    pub fn add_skipped(&mut self, url: String, reason: impl Into<String>) {
+        self.skipped.push(SkippedFile { url, reason: reason.into() });
+    }
+
     /// Get the number of successful downloads
     pub fn success_count(&self) -> usize {
         self.successful.len()
     }
 
+    /// Get the number of files that were already up to date
+    pub fn unchanged_count(&self) -> usize {
+        self.unchanged.len()
+    }
+
+    /// Get the number of files that are up to date locally, downloaded or unchanged
+    pub fn completed_count(&self) -> usize {
+        self.success_count() + self.unchanged_count()
+    }
+
+    /// Get the number of files skipped by policy
+    pub fn skipped_count(&self) -> usize {
+        self.skipped.len()
+    }
+
     /// Get the number of failed downloads
     pub fn failure_count(&self) -> usize {
         self.failed.len()
@@ -163,7 +513,7 @@ impl DownloadResult {
 
     /// Check if all downloads were successful
     pub fn all_successful(&self) -> bool {
-        self.failed.is_empty() && !self.successful.is_empty()
+        self.failed.is_empty() && self.completed_count() > 0
     }
 
     /// Get the total duration of the download process
@@ -189,30 +539,28 @@ impl DownloadResult {
         }
     }
 
-    /// Categorize error for better reporting
-    fn categorize_error(&self, error: &str) -> String {
-        if error.contains("404") || error.contains("not found") || error.contains("Not Found") {
-            "not_found".to_string()
-        } else if error.contains("timeout") || error.contains("Timeout") {
-            "timeout".to_string()
-        } else if error.contains("Permission denied") || error.contains("permission") {
-            "permission".to_string()
-        } else if error.contains("Network") || error.contains("Connection") {
-            "network".to_string()
-        } else if error.contains("403") || error.contains("Forbidden") {
-            "forbidden".to_string()
-        } else if error.contains("500") || error.contains("502") || error.contains("503") {
-            "server_error".to_string()
-        } else {
-            "other".to_string()
+    /// Add the files of another result, e.g. of another website
+    /// 
+    /// The start time of this result is kept.
+    pub fn merge(&mut self, other: DownloadResult) {
+        self.successful.extend(other.successful);
+        self.unchanged.extend(other.unchanged);
+        self.failed.extend(other.failed);
+        self.skipped.extend(other.skipped);
+        self.total_files += other.total_files;
+        self.total_bytes += other.total_bytes;
+        self.resumed_bytes += other.resumed_bytes;
+        self.planned.extend(other.planned);
+        if let Some(changes) = other.changes {
+            self.changes.get_or_insert_with(Changes::default).merge(changes);
         }
     }
 
     /// Get error statistics by category
-    pub fn error_stats(&self) -> HashMap<String, usize> {
-        let mut stats = HashMap::new();
-        for (_, _, error_type) in &self.failed {
-            *stats.entry(error_type.clone()).or_insert(0) += 1;
+    pub fn error_stats(&self) -> BTreeMap<ErrorKind, usize> {
+        let mut stats = BTreeMap::new();
+        for failure in &self.failed {
+            *stats.entry(failure.kind).or_insert(0) += 1;
         }
         stats
     }
@@ -232,16 +580,25 @@ impl fmt::Display for DownloadResult {
         } else {
             format!("{:.1}s", duration.as_secs_f64())
         };
-
+        
         writeln!(f, "📊 Download Summary:")?;
         writeln!(f, "   ⏱️  Total time: {duration_str}")?;
         writeln!(f, "   📁 Total files processed: {}", self.total_files)?;
         writeln!(f, "   ✅ Successful downloads: {}", self.success_count())?;
+        if !self.unchanged.is_empty() {
+            writeln!(f, "   ♻️  Unchanged files: {}", self.unchanged_count())?;
+        }
         writeln!(f, "   ❌ Failed downloads: {}", self.failure_count())?;
+        if !self.skipped.is_empty() {
+            writeln!(f, "   🚫 Skipped by policy: {}", self.skipped_count())?;
+        }
         writeln!(f, "   💾 Total data downloaded: {}", Self::format_bytes(self.total_bytes))?;
+        if self.resumed_bytes > 0 {
+            writeln!(f, "   ⏯️  Resumed from partial downloads: {}", Self::format_bytes(self.resumed_bytes))?;
+        }
         
         if self.total_files > 0 {
-            let success_rate = (self.success_count() as f64 / self.total_files as f64) * 100.0;
+            let success_rate = (self.completed_count() as f64 / self.total_files as f64) * 100.0;
             writeln!(f, "   📈 Success rate: {success_rate:.1}%")?;
             
             if duration.as_secs() > 0 && self.total_bytes > 0 {
@@ -258,6 +615,19 @@ impl fmt::Display for DownloadResult {
             }
         }
         
+        if !self.skipped.is_empty() {
+            writeln!(f, "\n🚫 Skipped by policy:")?;
+            for skipped in &self.skipped {
+                writeln!(f, "   • {}", skipped.url)?;
+                writeln!(f, "     → {}", skipped.reason)?;
+            }
+        }
+        
+        if let Some(changes) = self.changes.as_ref().filter(|changes| !changes.is_empty()) {
+            writeln!(f)?;
+            write!(f, "{changes}")?;
+        }
+        
         if !self.failed.is_empty() {
             writeln!(f, "\n❌ Failed downloads:")?;
             
@@ -265,65 +635,209 @@ impl fmt::Display for DownloadResult {
             let error_stats = self.error_stats();
             if error_stats.len() > 1 {
                 writeln!(f, "\n📊 Error breakdown:")?;
-                for (error_type, count) in &error_stats {
-                    let description = match error_type.as_str() {
-                        "not_found" => "Files not found (404)",
-                        "timeout" => "Network timeouts",
-                        "permission" => "Permission denied",
-                        "network" => "Network/connection errors",
-                        "forbidden" => "Access forbidden (403)",
-                        "server_error" => "Server errors (5xx)",
-                        _ => "Other errors",
-                    };
-                    writeln!(f, "   • {description}: {count} file(s)")?;
+                for (kind, count) in &error_stats {
+                    writeln!(f, "   • {}: {count} file(s)", kind.description())?;
                 }
                 writeln!(f)?;
             }
             
-            for (url, error, _) in &self.failed {
-                writeln!(f, "   • {url}")?;
-                writeln!(f, "     ❌ {error}")?;
+            for failure in &self.failed {
+                writeln!(f, "   • {}", failure.url)?;
+                writeln!(f, "     ❌ {}", failure.error)?;
             }
             
             writeln!(f, "\n💡 Troubleshooting recommendations:")?;
             
             let stats = self.error_stats();
-            if stats.contains_key("network") || stats.contains_key("timeout") {
+            if stats.keys().any(|kind| matches!(kind, ErrorKind::Network | ErrorKind::Timeout | ErrorKind::ConnectionRefused)) {
                 writeln!(f, "   🌐 Network issues detected:")?;
                 writeln!(f, "      • Check your internet connection stability")?;
                 writeln!(f, "      • Verify the server is accessible from your location")?;
                 writeln!(f, "      • Try again later if the server is overloaded")?;
             }
             
-            if stats.contains_key("not_found") {
+            if stats.contains_key(&ErrorKind::Dns) {
+                writeln!(f, "   🧭 DNS issues detected:")?;
+                writeln!(f, "      • Check that the host names in llms.txt are spelled correctly")?;
+                writeln!(f, "      • Verify your DNS settings or try another resolver")?;
+            }
+            
+            if stats.contains_key(&ErrorKind::Tls) {
+                writeln!(f, "   🔐 TLS issues detected:")?;
+                writeln!(f, "      • The server's certificate may be expired, self-signed or for another host")?;
+                writeln!(f, "      • A proxy intercepting HTTPS traffic may need its CA certificate installed")?;
+            }
+            
+            if stats.contains_key(&ErrorKind::NotFound) {
                 writeln!(f, "   📄 Missing files detected:")?;
                 writeln!(f, "      • Some files may have been moved or deleted")?;
                 writeln!(f, "      • Check if the llms.txt file is up to date")?;
                 writeln!(f, "      • Contact the website maintainer if many files are missing")?;
             }
             
-            if stats.contains_key("permission") {
+            if stats.contains_key(&ErrorKind::IoPermission) {
                 writeln!(f, "   🔒 Permission issues detected:")?;
                 writeln!(f, "      • Check write permissions for the download directory")?;
                 writeln!(f, "      • Try running with elevated privileges if necessary")?;
-                writeln!(f, "      • Ensure sufficient disk space is available")?;
             }
             
-            if stats.contains_key("forbidden") {
+            if stats.contains_key(&ErrorKind::DiskFull) {
+                writeln!(f, "   💽 Disk space issues detected:")?;
+                writeln!(f, "      • Free up space or choose another output directory")?;
+                writeln!(f, "      • Interrupted downloads are kept as .part files and resume on the next run")?;
+            }
+            
+            if stats.contains_key(&ErrorKind::Forbidden) || stats.contains_key(&ErrorKind::Auth) {
                 writeln!(f, "   🚫 Access restrictions detected:")?;
                 writeln!(f, "      • Some files may require authentication")?;
                 writeln!(f, "      • The server may be blocking automated requests")?;
                 writeln!(f, "      • Try accessing the files manually in a browser")?;
             }
             
-            if stats.contains_key("server_error") {
+            if stats.contains_key(&ErrorKind::RateLimited) {
+                writeln!(f, "   🐢 Rate limiting detected:")?;
+                writeln!(f, "      • The server is throttling requests")?;
+                writeln!(f, "      • Lower the number of concurrent downloads and try again later")?;
+            }
+            
+            if stats.contains_key(&ErrorKind::Server) {
                 writeln!(f, "   🔧 Server issues detected:")?;
                 writeln!(f, "      • The server is experiencing technical difficulties")?;
                 writeln!(f, "      • Try again later when the server is stable")?;
                 writeln!(f, "      • Contact the website administrator if issues persist")?;
             }
+            
+            if stats.contains_key(&ErrorKind::UnsafePath) {
+                writeln!(f, "   🛡️  Unsafe paths detected:")?;
+                writeln!(f, "      • Some URLs would be saved outside the output directory or under reserved names")?;
+                writeln!(f, "      • Keep files from other hosts in their own directory instead of rejecting them")?;
+            }
         }
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Outcome of one website of a batch
+#[derive(Debug)]
+pub struct SiteResult {
+    /// The website URL as given
+    pub url: String,
+    /// Per-file outcomes, or the error that stopped the website before any file was downloaded
+    pub outcome: Result<DownloadResult>,
+}
+
+impl SiteResult {
+    /// Get the host (and port) of the website, for per-domain summaries
+    pub fn domain(&self) -> String {
+        match url::Url::parse(&self.url) {
+            Ok(url) => match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{host}:{port}"),
+                (Some(host), None) => host.to_string(),
+                _ => self.url.clone(),
+            },
+            Err(_) => self.url.clone(),
+        }
+    }
+}
+
+/// Results of downloading several websites in one run
+#[derive(Debug)]
+pub struct BatchResult {
+    /// Outcome of every website, in the order they were given
+    pub sites: Vec<SiteResult>,
+    /// Start time for duration calculation
+    pub start_time: std::time::Instant,
+}
+
+impl BatchResult {
+    /// Create a new empty BatchResult
+    pub fn new() -> Self {
+        Self {
+            sites: Vec::new(),
+            start_time: std::time::Instant::now(),
+        }
+    }
+
+    /// Combine the files of all websites into a single result
+    pub fn combined(&self) -> DownloadResult {
+        let mut combined = DownloadResult::new();
+        combined.start_time = self.start_time;
+        for site in &self.sites {
+            if let Ok(result) = &site.outcome {
+                combined.merge(result.clone());
+            }
+        }
+        combined
+    }
+
+    /// Get the websites that failed before any file was downloaded
+    pub fn failed_sites(&self) -> impl Iterator<Item = (&SiteResult, &DownloadError)> {
+        self.sites.iter().filter_map(|site| site.outcome.as_ref().err().map(|e| (site, e)))
+    }
+
+    /// Get the number of websites that failed before any file was downloaded
+    pub fn failed_site_count(&self) -> usize {
+        self.failed_sites().count()
+    }
+}
+
+impl Default for BatchResult {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for BatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🌐 Sites: {} processed, {} failed", self.sites.len(), self.failed_site_count())?;
+        for site in &self.sites {
+            match &site.outcome {
+                Ok(result) => {
+                    let status = if result.failed.is_empty() { "✅" } else if result.completed_count() > 0 { "⚠️ " } else { "❌" };
+                    write!(f, "   {status} {}: {} downloaded", site.domain(), result.success_count())?;
+                    if !result.unchanged.is_empty() {
+                        write!(f, ", {} unchanged", result.unchanged_count())?;
+                    }
+                    write!(f, ", {} failed", result.failure_count())?;
+                    if !result.skipped.is_empty() {
+                        write!(f, ", {} skipped", result.skipped_count())?;
+                    }
+                    writeln!(f, " ({})", DownloadResult::format_bytes(result.total_bytes))?;
+                }
+                Err(e) => writeln!(f, "   ❌ {}: {e}", site.domain())?,
+            }
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.combined())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn llms_txt_unavailable_is_not_repeated_in_the_chain() {
+        let url = "https://example.com/llms.txt".to_string();
+        let error = DownloadError::LlmsTxtUnavailable {
+            url: url.clone(),
+            source: Box::new(DownloadError::HttpError { status: 404, url: url.clone() }),
+            tried: vec![url],
+        };
+        
+        let chain = error.chain();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].matches("File not found (404)").count(), 1);
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn messages_do_not_name_command_line_options() {
+        let url = "https://example.com/a.md".to_string();
+        for status in [401, 403, 404, 429, 500, 418] {
+            let message = DownloadError::HttpError { status, url: url.clone() }.to_string();
+            assert!(!message.contains("--"), "{message}");
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Something that happened to a single file during a download run
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DownloadEvent {
+    /// A download is about to start
+    Start {
+        /// URL of the file
+        url: String,
+        /// Where the file will be saved
+        path: String,
+        /// Position of the file in the run, starting at 1
+        index: usize,
+        /// Number of files in the run
+        total: usize,
+    },
+    /// More of the file has been written to disk
+    Progress {
+        /// URL of the file
+        url: String,
+        /// Bytes written so far, including bytes resumed from a partial file
+        bytes: u64,
+        /// Total size of the file, if the server announced it
+        total: Option<u64>,
+    },
+    /// An attempt failed and the request will be retried
+    Retry {
+        /// URL of the file
+        url: String,
+        /// Number of the failed attempt, starting at 1
+        attempt: u32,
+        /// Maximum number of retries
+        max_retries: u32,
+        /// Time until the next attempt in milliseconds
+        delay_ms: u64,
+        /// Why the attempt failed
+        error: String,
+    },
+    /// The file is done, one way or another
+    Finish {
+        /// URL of the file
+        url: String,
+        /// How the download ended
+        outcome: FinishOutcome,
+        /// Where the file was saved
+        path: Option<String>,
+        /// Bytes downloaded in this run
+        bytes: Option<u64>,
+        /// Why the download failed or was skipped
+        error: Option<String>,
+    },
+}
+
+/// How the download of a file ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishOutcome {
+    /// The file was downloaded
+    Downloaded,
+    /// The local copy was already up to date
+    Unchanged,
+    /// The file could not be downloaded
+    Failed,
+    /// The file was not downloaded because a policy forbids it
+    Skipped,
+}
+
+/// Receives download events as they happen
+/// 
+/// Events are emitted from concurrent download tasks, so implementations
+/// must be thread-safe and should return quickly.
+pub trait EventSink: Send + Sync {
+    /// Handle a single event
+    fn emit(&self, event: &DownloadEvent);
+}
+
+/// Writes events as newline-delimited JSON, one object per line
+/// 
+/// Each line carries an RFC 3339 `timestamp` next to the event fields, e.g.
+/// `{"timestamp":"2026-10-16T18:55:46Z","event":"start","url":"…",…}`.
+pub struct NdjsonWriter {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+/// An event line with the time it was written
+#[derive(Serialize)]
+struct EventLine<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a DownloadEvent,
+}
+
+impl NdjsonWriter {
+    /// Write events to standard output
+    pub fn stdout() -> Self {
+        Self::new(Box::new(io::stdout()))
+    }
+
+    /// Write events to a new file, replacing an existing one
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(Box::new(BufWriter::new(File::create(path)?))))
+    }
+
+    /// Write events to any writer
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl EventSink for NdjsonWriter {
+    fn emit(&self, event: &DownloadEvent) {
+        let line = EventLine {
+            timestamp: humantime::format_rfc3339_millis(SystemTime::now()).to_string(),
+            event,
+        };
+        let Ok(json) = serde_json::to_string(&line) else {
+            return;
+        };
+        
+        // Events are best effort, a closed pipe must not fail the downloads
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{json}").and_then(|_| writer.flush());
+        }
+    }
+}
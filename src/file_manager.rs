@@ -1,7 +1,12 @@
-use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::fs;
 use url::Url;
+use crate::cache::sha256_hex;
 use crate::error::DownloadError;
+use crate::manifest::MANIFEST_FILE_NAME;
+use crate::sync::QUARANTINE_DIR;
 
 /// Creates a local directory structure based on the domain from the URL
 /// 
@@ -17,28 +22,8 @@ use crate::error::DownloadError;
 /// 
 /// # Requirements
 /// * 3.1: Create local directory named after source domain
-pub fn create_local_directory(url: &str, output_dir: &str) -> Result<PathBuf, DownloadError> {
-    // Parse the URL to extract the domain
-    let parsed_url = Url::parse(url)
-        .map_err(|e| DownloadError::InvalidUrl(format!("Failed to parse URL: {e}")))?;
-    
-    // Extract the host (domain) from the URL
-    let host = parsed_url.host_str()
-        .ok_or_else(|| DownloadError::InvalidUrl("URL must have a valid host".to_string()))?;
-    
-    // Create the domain string, including port if present
-    let domain = if let Some(port) = parsed_url.port() {
-        format!("{host}_{port}")
-    } else {
-        host.to_string()
-    };
-    
-    // Sanitize the domain name for use as a directory name
-    let sanitized_domain = sanitize_filename(&domain);
-    
-    // Create the base output directory path
-    let base_dir = PathBuf::from(output_dir);
-    let domain_dir = base_dir.join(sanitized_domain);
+pub fn create_local_directory(url: &str, output_dir: &Path) -> Result<PathBuf, DownloadError> {
+    let domain_dir = local_directory(url, output_dir)?;
     
     // Create the directory structure if it doesn't exist
     fs::create_dir_all(&domain_dir)
@@ -47,10 +32,27 @@ pub fn create_local_directory(url: &str, output_dir: &str) -> Result<PathBuf, Do
     Ok(domain_dir)
 }
 
+/// Returns the domain directory for a URL without creating it
+/// 
+/// # Arguments
+/// * `url` - The base URL to extract the domain from
+/// * `output_dir` - The base output directory where files should be stored
+/// 
+/// # Returns
+/// * `Result<PathBuf, DownloadError>` - The path [`create_local_directory`] would create
+pub fn local_directory(url: &str, output_dir: &Path) -> Result<PathBuf, DownloadError> {
+    // Parse the URL to extract the domain
+    let parsed_url = Url::parse(url)
+        .map_err(|e| DownloadError::InvalidUrl(format!("Failed to parse URL: {e}")))?;
+    
+    Ok(output_dir.join(host_directory_name(&parsed_url)?))
+}
+
 /// Generates a local file path for a given URL, preserving the directory structure
 /// 
 /// This function takes a URL and a base directory, then creates a local file path
-/// that preserves the original directory structure from the URL.
+/// that preserves the original directory structure from the URL. The URL's own
+/// host is treated as the origin; use a [`PathMapper`] to map a whole llms.txt.
 /// 
 /// # Arguments
 /// * `url` - The full URL of the file to download
@@ -63,43 +65,522 @@ pub fn create_local_directory(url: &str, output_dir: &str) -> Result<PathBuf, Do
 /// * 3.2: Preserve relative path structure of downloaded files
 /// * 3.3: Handle file path sanitization
 pub fn get_local_file_path(url: &str, base_dir: &Path) -> Result<PathBuf, DownloadError> {
-    // Parse the URL to extract the path
-    let parsed_url = Url::parse(url)
-        .map_err(|e| DownloadError::InvalidUrl(format!("Failed to parse URL: {e}")))?;
+    let local_path = PathMapper::new(base_dir, url, CrossOriginPolicy::Reject)?.map(url)?;
+    
+    // Ensure the parent directory exists
+    if let Some(parent) = local_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(DownloadError::IoError)?;
+    }
+    
+    Ok(local_path)
+}
+
+/// What to do with llms.txt entries hosted on another host than llms.txt itself
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CrossOriginPolicy {
+    /// Save them under `_external/<host>/` inside the domain directory
+    #[default]
+    HostDirectory,
+    /// Do not download them
+    Reject,
+}
+
+/// Directory inside the domain directory holding files from other hosts
+pub const EXTERNAL_DIR: &str = "_external";
+
+/// File name used for URLs ending in `/`
+pub const INDEX_FILE_NAME: &str = "index.md";
+
+/// Name of the mapping manifest stored in each domain directory
+pub const MAPPING_FILE_NAME: &str = ".llmsdl-mapping.json";
+
+/// Number of hex digits of the query string and URL hashes added to file names
+const QUERY_HASH_LENGTH: usize = 8;
+
+/// Longest file name most filesystems accept, in bytes
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Longest name of a downloaded file, in bytes
+/// 
+/// Leaves room for the `.part.json` suffix of the metadata kept next to a
+/// partial download.
+const MAX_FILE_NAME_LENGTH: usize = MAX_NAME_LENGTH - ".part.json".len();
+
+/// Maps the URLs listed in an llms.txt to local paths inside a base directory
+/// 
+/// Every mapped path is guaranteed to stay inside `base_dir`: URL path
+/// segments are sanitized, `.`/`..` segments and Windows reserved names
+/// (`CON`, `NUL`, `COM1`, ...) are rejected, and symlinks inside `base_dir`
+/// may not lead out of it. Entries on other hosts are handled according to
+/// the [`CrossOriginPolicy`].
+/// 
+/// The mapping is deterministic: URLs ending in `/` are saved as `index.md`,
+/// query strings are encoded into the file name as a short SHA-256 hash
+/// (`docs?page=2` becomes `docs_<hash>`) and fragments are ignored. Paths
+/// that only differ in case get a `~<hash>` suffix derived from the URL, so
+/// downloads do not overwrite each other on case-insensitive filesystems.
+/// Which of them keeps the plain name is taken from the mapping manifest of
+/// earlier runs (see [`PathMapper::with_previous`]), so reordering llms.txt
+/// does not swap local names. File names that are too long for the
+/// filesystem are shortened, keeping these suffixes and the extension.
+/// Mapping the same URL twice returns the same path. No files or directories
+/// are created.
+#[derive(Debug, Clone)]
+pub struct PathMapper {
+    base_dir: PathBuf,
+    host: Option<String>,
+    port: Option<u16>,
+    cross_origin: CrossOriginPolicy,
+    /// Lowercased relative paths already handed out
+    taken: HashSet<String>,
+    /// Lowercased relative paths of the directories these are in
+    directories: HashSet<String>,
+    /// Paths already handed out, by URL
+    mapped: HashMap<String, PathBuf>,
+    /// URLs of earlier runs, by lowercased relative path
+    previous: HashMap<String, String>,
+}
+
+impl PathMapper {
+    /// Create a mapper for the files of the website at `origin_url`
+    /// 
+    /// # Arguments
+    /// * `base_dir` - The domain directory files are saved in
+    /// * `origin_url` - The URL of the website (or its llms.txt)
+    /// * `cross_origin` - How to handle entries hosted elsewhere
+    pub fn new(base_dir: &Path, origin_url: &str, cross_origin: CrossOriginPolicy) -> Result<Self, DownloadError> {
+        let origin = Url::parse(origin_url)
+            .map_err(|e| DownloadError::InvalidUrl(format!("Failed to parse URL: {e}")))?;
+        
+        Ok(Self {
+            base_dir: base_dir.to_path_buf(),
+            host: origin.host_str().map(str::to_ascii_lowercase),
+            port: origin.port(),
+            cross_origin,
+            // Keep the names of the download manifest and the quarantine directory free
+            taken: HashSet::from([MANIFEST_FILE_NAME.to_string(), QUARANTINE_DIR.to_string()]),
+            directories: HashSet::new(),
+            mapped: HashMap::new(),
+            previous: HashMap::new(),
+        })
+    }
+
+    /// Keep the local paths an earlier run gave to URLs that share a name
+    /// 
+    /// A URL gets back the name it was saved under before, and a name an
+    /// earlier run gave to another URL is not handed out to a new one.
+    /// 
+    /// # Arguments
+    /// * `mapping` - The mapping manifest of the domain directory
+    pub fn with_previous(mut self, mapping: &MappingManifest) -> Self {
+        self.previous = mapping
+            .files
+            .iter()
+            .filter_map(|(relative_path, url)| {
+                let mut url = Url::parse(url).ok()?;
+                url.set_fragment(None);
+                Some((relative_path.to_lowercase(), url.to_string()))
+            })
+            .collect();
+        self
+    }
+
+    /// Get the local path for a URL
+    /// 
+    /// # Arguments
+    /// * `url` - The full URL of the file to download
+    /// 
+    /// # Returns
+    /// * `Result<PathBuf, DownloadError>` - The path inside the base directory, or
+    ///   [`DownloadError::UnsafePath`] if the URL cannot be mapped safely
+    pub fn map(&mut self, url: &str) -> Result<PathBuf, DownloadError> {
+        let mut parsed_url = Url::parse(url)
+            .map_err(|e| DownloadError::InvalidUrl(format!("Failed to parse URL: {e}")))?;
+        
+        // Fragments point into the same document
+        parsed_url.set_fragment(None);
+        let key = parsed_url.to_string();
+        if let Some(local_path) = self.mapped.get(&key) {
+            return Ok(local_path.clone());
+        }
+        
+        let mut components = Vec::new();
+        
+        // Files from other hosts never end up next to the website's own files
+        let same_origin = parsed_url.host_str().map(str::to_ascii_lowercase) == self.host
+            && parsed_url.port() == self.port;
+        if !same_origin {
+            match self.cross_origin {
+                CrossOriginPolicy::HostDirectory => {
+                    components.push(EXTERNAL_DIR.to_string());
+                    components.push(host_directory_name(&parsed_url)?);
+                }
+                CrossOriginPolicy::Reject => {
+                    return Err(DownloadError::UnsafePath(format!(
+                        "{url} is on a different host than llms.txt"
+                    )));
+                }
+            }
+        }
+        
+        // Split the path into components and sanitize each part
+        for segment in parsed_url.path().split('/').filter(|segment| !segment.is_empty()) {
+            components.push(safe_component(segment, url)?);
+        }
+        
+        // Directory-style URLs, including the root, are saved as their index file
+        if parsed_url.path().ends_with('/') {
+            components.push(INDEX_FILE_NAME.to_string());
+        }
+        
+        // Different query strings are different files
+        if let Some(query) = parsed_url.query().filter(|query| !query.is_empty()) {
+            if let Some(file_name) = components.last_mut() {
+                let (stem, extension) = split_extension(file_name);
+                let hash = sha256_hex(query.as_bytes());
+                let suffix = format!("_{}{extension}", &hash[..QUERY_HASH_LENGTH]);
+                *file_name = shorten_file_name(stem, &suffix, MAX_FILE_NAME_LENGTH);
+            }
+        }
+        
+        let local_path = self.claim_path(components, &key)?;
+        self.mapped.insert(key, local_path.clone());
+        Ok(local_path)
+    }
+
+    /// Add an extension to a mapped path that has none
+    /// 
+    /// Used once the `Content-Type` of a file is known. The new name is
+    /// reserved like any other mapped path.
+    /// 
+    /// # Arguments
+    /// * `local_path` - A path returned by [`PathMapper::map`]
+    /// * `extension` - The extension to add, without the leading dot
+    /// 
+    /// # Returns
+    /// * `Result<PathBuf, DownloadError>` - The path with the extension
+    pub fn with_extension(&mut self, local_path: &Path, extension: &str) -> Result<PathBuf, DownloadError> {
+        let display = local_path.display().to_string();
+        let relative = local_path
+            .strip_prefix(&self.base_dir)
+            .map_err(|_| DownloadError::UnsafePath(format!("{display} is not inside {}", self.base_dir.display())))?;
+        
+        let mut components: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if let Some(file_name) = components.last_mut() {
+            file_name.push('.');
+            file_name.push_str(extension);
+        }
+        
+        let url = self
+            .mapped
+            .iter()
+            .find(|(_, mapped)| mapped.as_path() == local_path)
+            .map_or_else(|| display.clone(), |(url, _)| url.clone());
+        let extended = self.claim_path(components, &url)?;
+        for local_path in self.mapped.values_mut() {
+            if local_path.as_path() == Path::new(&display) {
+                *local_path = extended.clone();
+            }
+        }
+        Ok(extended)
+    }
+
+    /// Build the path for some components and reserve it
+    fn claim_path(&mut self, mut components: Vec<String>, url: &str) -> Result<PathBuf, DownloadError> {
+        // Build the local file path
+        let mut local_path = self.base_dir.clone();
+        for component in &components {
+            local_path.push(component);
+        }
+        ensure_inside(&self.base_dir, &local_path, url)?;
+        
+        // Only the file name changes when the path is already taken
+        if let Some(file_name) = self.claim(&mut components, url)? {
+            local_path.set_file_name(file_name);
+        }
+        
+        Ok(local_path)
+    }
+
+    /// Reserve a relative path, compared case-insensitively
+    /// 
+    /// A file name is taken if another URL maps to it, an earlier run gave it
+    /// to another URL, or it is needed as a directory. A taken name gets a
+    /// `~<hash>` suffix derived from the URL, unless an earlier run saved the
+    /// URL under the suffixed name. Names that are too long are shortened,
+    /// keeping their extension. Returns the new file name if the name was
+    /// shortened or the path was already taken.
+    fn claim(&mut self, components: &mut [String], url: &str) -> Result<Option<String>, DownloadError> {
+        let Some(file_name) = components.last().cloned() else {
+            return Ok(None);
+        };
+        let (stem, extension) = split_extension(&file_name);
+        
+        // Directories cannot be renamed, a file already using the name wins
+        let directories: Vec<String> = (1..components.len())
+            .map(|depth| components[..depth].join("/").to_lowercase())
+            .collect();
+        if let Some(conflict) = directories.iter().find(|directory| self.taken.contains(*directory)) {
+            return Err(DownloadError::UnsafePath(format!(
+                "{url} needs '{conflict}' as a directory, but it is already used for another file"
+            )));
+        }
+        
+        // The hash keeps the name of each URL the same whichever order they are mapped in
+        let hash = sha256_hex(url.as_bytes());
+        let hash = &hash[..QUERY_HASH_LENGTH];
+        let name = |attempt: usize| match attempt {
+            0 => shorten_file_name(stem, extension, MAX_FILE_NAME_LENGTH),
+            1 => shorten_file_name(stem, &format!("~{hash}{extension}"), MAX_FILE_NAME_LENGTH),
+            n => shorten_file_name(stem, &format!("~{hash}~{n}{extension}"), MAX_FILE_NAME_LENGTH),
+        };
+        let parent: String = components[..components.len() - 1].iter().map(|component| format!("{component}/")).collect();
+        let previous_owner = |attempt: usize| self.previous.get(&format!("{parent}{}", name(attempt)).to_lowercase());
+        
+        // A name the URL had before comes first, names other URLs had are skipped
+        let kept = (0..2).find(|&attempt| previous_owner(attempt).is_some_and(|owner| owner == url));
+        let attempts = kept.into_iter().chain((0..).filter(|&attempt| {
+            Some(attempt) != kept && previous_owner(attempt).is_none_or(|owner| owner == url)
+        }));
+        for attempt in attempts {
+            if let Some(last) = components.last_mut() {
+                *last = name(attempt);
+            }
+            let key = components.join("/").to_lowercase();
+            let is_directory = self.directories.contains(&key)
+                || components.iter().fold(self.base_dir.clone(), |path, component| path.join(component)).is_dir();
+            if !is_directory && self.taken.insert(key) {
+                break;
+            }
+        }
+        
+        self.directories.extend(directories);
+        Ok(components.last().filter(|last| **last != file_name).cloned())
+    }
+}
+
+/// Record of the URL every local file was downloaded from
+/// 
+/// Stored as a JSON file in each domain directory, keyed by the path of the
+/// file relative to that directory (always with `/` separators).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MappingManifest {
+    /// Original URL, by relative local path
+    pub files: BTreeMap<String, String>,
+}
+
+impl MappingManifest {
+    /// Load the mapping manifest stored in a domain directory
+    /// 
+    /// A missing or unreadable manifest results in an empty one.
+    pub fn load(domain_dir: &Path) -> Self {
+        fs::read_to_string(domain_dir.join(MAPPING_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record the URL a file inside the domain directory was downloaded from
+    pub fn insert(&mut self, domain_dir: &Path, local_path: &Path, url: &str) {
+        if let Some(relative) = relative_path(domain_dir, local_path) {
+            self.files.insert(relative, url.to_string());
+        }
+    }
+
+    /// Get the URL a file was downloaded from
+    pub fn url_for(&self, relative_path: &str) -> Option<&str> {
+        self.files.get(relative_path).map(String::as_str)
+    }
+
+    /// Write the manifest to the domain directory, dropping files that no longer exist
+    pub fn save(&mut self, domain_dir: &Path) -> Result<(), DownloadError> {
+        self.files.retain(|relative_path, _| domain_dir.join(relative_path).is_file());
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| DownloadError::ParseError(format!("Failed to serialize mapping manifest: {e}")))?;
+        fs::write(domain_dir.join(MAPPING_FILE_NAME), content)?;
+        Ok(())
+    }
+}
+
+/// Returns the path of a file relative to the domain directory, with `/` separators
+/// 
+/// # Arguments
+/// * `domain_dir` - The domain directory
+/// * `local_path` - A path inside the domain directory
+/// 
+/// # Returns
+/// * `Option<String>` - The relative path, or `None` if the file is not inside `domain_dir`
+pub fn relative_path(domain_dir: &Path, local_path: &Path) -> Option<String> {
+    let relative: Vec<_> = local_path
+        .strip_prefix(domain_dir)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    Some(relative.join("/"))
+}
+
+/// Picks a file extension for a `Content-Type` header value
+/// 
+/// # Arguments
+/// * `content_type` - The header value, parameters such as `charset` are ignored
+/// 
+/// # Returns
+/// * `Option<&'static str>` - The extension without the leading dot, if the type is known
+pub fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
     
-    // Get the path from the URL, removing the leading slash
-    let url_path = parsed_url.path();
-    let clean_path = if let Some(stripped) = url_path.strip_prefix('/') {
-        stripped
+    let extension = match mime.as_str() {
+        "text/markdown" | "text/x-markdown" => "md",
+        "text/plain" => "txt",
+        "text/html" | "application/xhtml+xml" => "html",
+        "text/csv" => "csv",
+        "text/xml" | "application/xml" => "xml",
+        "text/yaml" | "application/yaml" | "application/x-yaml" => "yaml",
+        "application/json" => "json",
+        "application/pdf" => "pdf",
+        _ => return None,
+    };
+    Some(extension)
+}
+
+/// Returns the path used for a file while it is being downloaded
+/// 
+/// The partial file sits next to the final file with a `.part` suffix,
+/// so an interrupted download never leaves a truncated file in place.
+/// 
+/// # Arguments
+/// * `local_path` - The final location of the file
+/// 
+/// # Returns
+/// * `PathBuf` - The path of the partial file
+pub fn partial_file_path(local_path: &Path) -> PathBuf {
+    let mut file_name = local_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".part");
+    local_path.with_file_name(file_name)
+}
+
+/// Turns a document title into a lowercase, dash-separated file stem
+/// 
+/// Used to name documents split out of llms-full.txt when their original
+/// URL is unknown.
+/// 
+/// # Arguments
+/// * `title` - The document title
+/// 
+/// # Returns
+/// * `String` - The slug, or "untitled" if the title has no usable characters
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    
+    for ch in title.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "untitled".to_string()
     } else {
-        url_path
+        slug.to_string()
+    }
+}
+
+/// Builds the directory name for a URL's host, including the port if present
+fn host_directory_name(url: &Url) -> Result<String, DownloadError> {
+    // Extract the host (domain) from the URL
+    let host = url.host_str()
+        .ok_or_else(|| DownloadError::InvalidUrl("URL must have a valid host".to_string()))?;
+    
+    // Create the domain string, including port if present
+    let domain = if let Some(port) = url.port() {
+        format!("{host}_{port}")
+    } else {
+        host.to_string()
     };
     
-    // Handle empty path or root path
-    if clean_path.is_empty() || clean_path == "/" {
-        return Ok(base_dir.join("index.html"));
+    // Sanitize the domain name for use as a directory name
+    Ok(sanitize_filename(&domain))
+}
+
+/// Splits a file name into its stem and extension (including the dot)
+pub(crate) fn split_extension(file_name: &str) -> (&str, &str) {
+    match file_name.rfind('.') {
+        Some(dot) if dot > 0 => file_name.split_at(dot),
+        _ => (file_name, ""),
+    }
+}
+
+/// Sanitizes one URL path segment, rejecting segments that are unsafe on disk
+fn safe_component(segment: &str, url: &str) -> Result<String, DownloadError> {
+    if segment == "." || segment == ".." {
+        return Err(DownloadError::UnsafePath(format!("{url} contains a '{segment}' path segment")));
+    }
+    
+    let component = sanitize_filename(segment);
+    if is_reserved_name(&component) {
+        return Err(DownloadError::UnsafePath(format!(
+            "{url} contains '{component}', which is a reserved file name on Windows"
+        )));
     }
     
-    // Split the path into components and sanitize each part
-    let path_components: Vec<String> = clean_path
-        .split('/')
-        .filter(|component| !component.is_empty())
-        .map(sanitize_filename)
-        .collect();
+    Ok(component)
+}
+
+/// Checks for names Windows reserves for devices, with or without an extension
+fn is_reserved_name(name: &str) -> bool {
+    const RESERVED: &[&str] = &["CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$"];
     
-    // Build the local file path
-    let mut local_path = base_dir.to_path_buf();
-    for component in path_components {
-        local_path = local_path.join(component);
+    let stem = name.split('.').next().unwrap_or_default().trim_end().to_ascii_uppercase();
+    if RESERVED.contains(&stem.as_str()) {
+        return true;
     }
     
-    // Ensure the parent directory exists
-    if let Some(parent) = local_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(DownloadError::IoError)?;
+    // COM1-COM9 and LPT1-LPT9, including the superscript digits Windows also accepts
+    match (stem.get(..3), stem.get(3..)) {
+        (Some("COM" | "LPT"), Some(digit)) => {
+            matches!(digit, "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "¹" | "²" | "³")
+        }
+        _ => false,
     }
+}
+
+/// Makes sure a mapped path cannot end up outside the base directory
+fn ensure_inside(base_dir: &Path, local_path: &Path, url: &str) -> Result<(), DownloadError> {
+    let escapes = match local_path.strip_prefix(base_dir) {
+        Ok(relative) => !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_))),
+        Err(_) => true,
+    };
     
-    Ok(local_path)
+    // Symlinks inside the base directory must not lead outside of it either
+    let escapes = escapes
+        || match base_dir.canonicalize() {
+            Ok(canonical_base) => local_path
+                .ancestors()
+                .take_while(|ancestor| ancestor.starts_with(base_dir))
+                .find(|ancestor| ancestor.exists())
+                .and_then(|existing| existing.canonicalize().ok())
+                .is_some_and(|existing| !existing.starts_with(&canonical_base)),
+            Err(_) => false,
+        };
+    
+    if escapes {
+        return Err(DownloadError::UnsafePath(format!(
+            "{url} would be saved outside of {}",
+            base_dir.display()
+        )));
+    }
+    
+    Ok(())
 }
 
 /// Sanitizes a filename by removing or replacing invalid characters
@@ -144,10 +625,225 @@ fn sanitize_filename(filename: &str) -> String {
     }
     
     // Limit length to avoid filesystem issues
-    if sanitized.len() > 255 {
-        sanitized.truncate(255);
+    if sanitized.len() > MAX_NAME_LENGTH {
+        let (stem, extension) = split_extension(&sanitized);
+        sanitized = shorten_file_name(stem, extension, MAX_NAME_LENGTH);
     }
     
     sanitized
 }
 
+/// Joins a file stem and a suffix, cutting the stem so the result fits into
+/// `max_length` bytes
+/// 
+/// The suffix holds the extension and anything added in front of it, such
+/// as the query or URL hash, so those survive the cut. A suffix
+/// that does not fit by itself is cut as well.
+fn shorten_file_name(stem: &str, suffix: &str, max_length: usize) -> String {
+    let mut name = match max_length.checked_sub(suffix.len()) {
+        Some(stem_length) if stem_length > 0 => {
+            format!("{}{suffix}", &stem[..floor_char_boundary(stem, stem_length)])
+        }
+        _ => format!("{stem}{suffix}"),
+    };
+    name.truncate(floor_char_boundary(&name, max_length));
+    name
+}
+
+/// Get the largest char boundary of a string at or below an index
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    (0..=index).rev().find(|&index| text.is_char_boundary(index)).unwrap_or_default()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    
+    const ORIGIN: &str = "https://example.com/llms.txt";
+
+    fn base_dir() -> PathBuf {
+        std::env::temp_dir().join("llmsdl-path-mapper").join("example.com")
+    }
+
+    fn mapper() -> PathMapper {
+        PathMapper::new(&base_dir(), ORIGIN, CrossOriginPolicy::HostDirectory).unwrap()
+    }
+
+    /// The suffix of a URL whose name is already taken
+    fn url_suffix(url: &str) -> String {
+        format!("~{}", &sha256_hex(url.as_bytes())[..QUERY_HASH_LENGTH])
+    }
+
+    /// URL path segments, biased towards the ones that are hard to map safely
+    fn segment() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("..".to_string()),
+            Just(".".to_string()),
+            Just("%2e%2e".to_string()),
+            Just("%2E.".to_string()),
+            Just("..\\..\\etc".to_string()),
+            Just("\\windows".to_string()),
+            Just("C:".to_string()),
+            Just("CON".to_string()),
+            Just("nul".to_string()),
+            Just("COM1.txt".to_string()),
+            Just("Lpt9.md".to_string()),
+            Just("Doc.md".to_string()),
+            Just("doc.md".to_string()),
+            Just("DOC.MD".to_string()),
+            "[a-zA-Z0-9._~%\\\\:-]{1,12}",
+            any::<String>(),
+            // Long names, some with multi-byte characters and long extensions
+            "[a-zé]{200,300}(\\.md|\\.[a-z]{1,40})?",
+        ]
+    }
+
+    /// URLs on the origin and elsewhere, with absolute-looking paths and queries
+    fn url() -> impl Strategy<Value = String> {
+        let prefix = prop_oneof![
+            Just("https://example.com/"),
+            Just("https://example.com//"),
+            Just("https://EXAMPLE.com/"),
+            Just("https://other.example/"),
+            Just("https://example.com:8443/"),
+        ];
+        let query = prop_oneof![Just(""), Just("?page=2"), Just("?"), Just("#section")];
+        (prefix, prop::collection::vec(segment(), 0..5), any::<bool>(), query).prop_map(
+            |(prefix, segments, trailing_slash, query)| {
+                let slash = if trailing_slash { "/" } else { "" };
+                format!("{prefix}{}{slash}{query}", segments.join("/"))
+            },
+        )
+    }
+    
+    proptest! {
+        #[test]
+        fn mapped_paths_stay_inside_base_dir(url in url()) {
+            if let Ok(local_path) = mapper().map(&url) {
+                let relative = local_path.strip_prefix(base_dir()).unwrap();
+                prop_assert!(relative.components().count() > 0);
+                for component in relative.components() {
+                    let Component::Normal(name) = component else {
+                        return Err(TestCaseError::fail(format!("{url} mapped to {}", local_path.display())));
+                    };
+                    let name = name.to_string_lossy();
+                    prop_assert!(!is_reserved_name(&name), "{url} mapped to reserved name {name}");
+                    prop_assert!(name.len() <= MAX_NAME_LENGTH, "{url} mapped to a {} byte name", name.len());
+                    prop_assert!(!name.contains(['\\', '/', ':']), "{url} mapped to {name}");
+                }
+            }
+        }
+
+        #[test]
+        fn mapped_paths_are_distinct_ignoring_case(urls in prop::collection::vec(url(), 1..20)) {
+            let mut mapper = mapper();
+            let mut paths: HashMap<String, String> = HashMap::new();
+            for url in &urls {
+                let Ok(local_path) = mapper.map(url) else { continue };
+                let key = local_path.to_string_lossy().to_lowercase();
+                let mut canonical = Url::parse(url).unwrap();
+                canonical.set_fragment(None);
+                if let Some(other) = paths.insert(key, canonical.to_string()) {
+                    prop_assert_eq!(&other, &canonical.to_string(), "both map to {}", local_path.display());
+                }
+            }
+        }
+
+        #[test]
+        fn mapping_is_deterministic(urls in prop::collection::vec(url(), 1..20)) {
+            let mut first = mapper();
+            let mut second = mapper();
+            for url in &urls {
+                let local_path = first.map(url).ok();
+                prop_assert_eq!(&local_path, &first.map(url).ok());
+                prop_assert_eq!(&local_path, &second.map(url).ok());
+            }
+        }
+
+        #[test]
+        fn file_names_leave_room_for_partial_downloads(urls in prop::collection::vec(url(), 1..10)) {
+            let mut mapper = mapper();
+            for url in &urls {
+                let Ok(local_path) = mapper.map(url) else { continue };
+                let extended = mapper.with_extension(&local_path, "yaml");
+                for path in std::iter::once(&local_path).chain(extended.as_ref().ok()) {
+                    let name = path.file_name().unwrap().to_string_lossy();
+                    prop_assert!(name.len() + ".part.json".len() <= MAX_NAME_LENGTH, "{url} mapped to {}", path.display());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reserved_names_are_rejected() {
+        for url in ["https://example.com/CON", "https://example.com/docs/NUL", "https://example.com/COM1.txt"] {
+            assert!(matches!(mapper().map(url), Err(DownloadError::UnsafePath(_))), "{url}");
+        }
+    }
+
+    #[test]
+    fn long_names_keep_their_suffixes() {
+        let stem = "a".repeat(300);
+        let mut mapper = mapper();
+        
+        let first = mapper.map(&format!("https://example.com/{stem}.md")).unwrap();
+        let second = mapper.map(&format!("https://example.com/{stem}b.md")).unwrap();
+        let query = mapper.map(&format!("https://example.com/{stem}.md?page=2")).unwrap();
+        
+        let name = |path: &Path| path.file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(name(&first), format!("{}.md", "a".repeat(MAX_FILE_NAME_LENGTH - 3)));
+        let suffix = url_suffix(&format!("https://example.com/{stem}b.md"));
+        assert_eq!(name(&second), format!("{}{suffix}.md", "a".repeat(MAX_FILE_NAME_LENGTH - suffix.len() - 3)));
+        assert!(name(&query).ends_with(&format!("_{}.md", &sha256_hex(b"page=2")[..QUERY_HASH_LENGTH])));
+        assert_eq!(name(&query).len(), MAX_FILE_NAME_LENGTH);
+    }
+
+    #[test]
+    fn shortens_names_at_char_boundaries() {
+        assert_eq!(shorten_file_name("ééé", ".md", 7), "éé.md");
+        assert_eq!(shorten_file_name("ééé", ".md", 6), "é.md");
+        assert_eq!(shorten_file_name("abc", ".md", 10), "abc.md");
+        // A suffix that does not fit by itself is cut too
+        assert_eq!(shorten_file_name("abc", ".markdown", 5), "abc.m");
+    }
+
+    #[test]
+    fn names_differing_in_case_get_a_suffix() {
+        let mut mapper = mapper();
+        let first = mapper.map("https://example.com/Doc.md").unwrap();
+        let second = mapper.map("https://example.com/doc.md").unwrap();
+        
+        assert_eq!(first, base_dir().join("Doc.md"));
+        assert_eq!(second, base_dir().join(format!("doc{}.md", url_suffix("https://example.com/doc.md"))));
+    }
+
+    #[test]
+    fn earlier_runs_keep_colliding_names_in_either_order() {
+        let (upper, lower) = ("https://example.com/Doc.md", "https://example.com/doc.md");
+        let mut first_run = mapper();
+        let upper_path = first_run.map(upper).unwrap();
+        let lower_path = first_run.map(lower).unwrap();
+        let mut mapping = MappingManifest::default();
+        mapping.insert(&base_dir(), &upper_path, upper);
+        mapping.insert(&base_dir(), &lower_path, &format!("{lower}#intro"));
+        
+        for order in [[upper, lower], [lower, upper]] {
+            let mut mapper = mapper().with_previous(&mapping);
+            let paths: HashMap<&str, PathBuf> = order.iter().map(|url| (*url, mapper.map(url).unwrap())).collect();
+            assert_eq!(paths[upper], upper_path, "{order:?}");
+            assert_eq!(paths[lower], lower_path, "{order:?}");
+        }
+        
+        // A new URL does not take a name an earlier run gave to another one
+        let mut mapper = mapper().with_previous(&mapping);
+        let new = "https://example.com/DOC.md";
+        assert_eq!(mapper.map(new).unwrap(), base_dir().join(format!("DOC{}.md", url_suffix(new))));
+        assert_eq!(mapper.map(upper).unwrap(), upper_path);
+        assert_eq!(mapper.map(lower).unwrap(), lower_path);
+    }
+}
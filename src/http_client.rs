@@ -1,77 +1,475 @@
+use crate::auth::{Credentials, HostPattern};
+use crate::cache::{to_hex, FileMetadata, PartialDownload};
 use crate::error::{DownloadError, Result};
-use reqwest::Client;
-use std::path::Path;
-use std::time::Duration;
+use crate::events::{DownloadEvent, EventSink};
+use crate::file_manager::partial_file_path;
+use crate::robots::RobotsTxt;
+use crate::throttle::{HostLimits, HostThrottle};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE,
+    LAST_MODIFIED, RANGE, RETRY_AFTER,
+};
+use reqwest::redirect::Policy;
+use reqwest::{Certificate, Client, Identity, NoProxy, Proxy, RequestBuilder, Response, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use url::Url;
+use sha2::{Digest, Sha256};
 use tokio::fs;
-use tokio::time::sleep;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{sleep, timeout};
 use indicatif::{ProgressBar, ProgressStyle};
 
+/// Outcome of a conditional download
+#[derive(Debug, Clone)]
+pub enum DownloadOutcome {
+    /// The file was downloaded and written to disk
+    Downloaded {
+        /// Metadata of the complete file
+        metadata: FileMetadata,
+        /// Bytes reused from a partial file left by an earlier attempt
+        resumed_bytes: u64,
+        /// HTTP status of the response the file was written from
+        status: u16,
+    },
+    /// The server reported that the local copy is still current
+    NotModified,
+}
+
+/// Minimum time between two progress events for the same file
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Longest `Retry-After` delay that is honored
+/// 
+/// Servers occasionally ask for hours; waiting that long is never useful
+/// for a download run, so longer delays are cut to this.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Default `User-Agent` header, e.g. `llmsdl/0.1.0`
+pub const DEFAULT_USER_AGENT: &str = concat!("llmsdl/", env!("CARGO_PKG_VERSION"));
+
+/// Maximum number of redirects followed for a single request
+const MAX_REDIRECTS: usize = 10;
+
+/// Receives human-readable status messages, such as retry notices
+pub type StatusCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
 /// HTTP client with retry logic and error handling
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
+    read_timeout: Duration,
+    file_timeout: Option<Duration>,
     max_retries: u32,
-    base_delay: Duration,
+    backoff_base: Duration,
+    backoff_cap: Duration,
+    events: Option<Arc<dyn EventSink>>,
+    /// Receives retry notices when there is no event sink
+    status: Option<StatusCallback>,
+    /// Per-host limits and pauses, shared by all clones
+    throttle: Arc<HostThrottle>,
+    user_agent: String,
+    /// robots.txt rules by origin, shared by all clones
+    robots: Arc<Mutex<HashMap<String, Arc<RobotsTxt>>>>,
+    /// Host-scoped headers, netrc logins and cookies
+    credentials: Arc<Credentials>,
 }
 
-impl HttpClient {
-    /// Create a new HTTP client with default settings
+/// Builder for [`HttpClient`]
+#[derive(Debug, Clone)]
+pub struct HttpClientBuilder {
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    file_timeout: Option<Duration>,
+    max_retries: u32,
+    backoff_base: Duration,
+    backoff_cap: Duration,
+    user_agent: String,
+    headers: Vec<(String, String)>,
+    host_headers: Vec<(HostPattern, String, String)>,
+    netrc: Option<PathBuf>,
+    cookie_jar: Option<PathBuf>,
+    host_limits: HostLimits,
+    proxy: Option<String>,
+    no_proxy: Option<String>,
+    ca_certs: Vec<PathBuf>,
+    client_cert: Option<(PathBuf, PathBuf)>,
+    insecure: bool,
+}
+
+impl HttpClientBuilder {
+    /// Create a builder with default settings
+    /// 
+    /// Defaults to a 10 s connect timeout, a 30 s read timeout, no deadline
+    /// per file, and 3 retries with a backoff starting at 500 ms and capped
+    /// at 30 s.
     pub fn new() -> Self {
         Self {
-            client: Client::new(),
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            file_timeout: None,
             max_retries: 3,
-            base_delay: Duration::from_millis(500),
+            backoff_base: Duration::from_millis(500),
+            backoff_cap: Duration::from_secs(30),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: Vec::new(),
+            host_headers: Vec::new(),
+            netrc: None,
+            cookie_jar: None,
+            host_limits: HostLimits::default(),
+            proxy: None,
+            no_proxy: None,
+            ca_certs: Vec::new(),
+            client_cert: None,
+            insecure: false,
         }
     }
 
+    /// Set how long to wait for a connection to be established
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Set how long to wait for the response headers or the next chunk of the body
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Set a deadline for fetching a single file, including all retries
+    /// 
+    /// Without a deadline a slow but steady transfer may take any amount of time.
+    pub fn file_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.file_timeout = timeout;
+        self
+    }
+
+    /// Set how often a failed request is retried
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the base delay of the exponential backoff between retries
+    /// 
+    /// Retry `n` waits a random time between zero and `base * 2^n`, at most
+    /// the cap set with [`HttpClientBuilder::backoff_cap`] ("full jitter").
+    pub fn backoff_base(mut self, delay: Duration) -> Self {
+        self.backoff_base = delay;
+        self
+    }
+
+    /// Set the longest delay between two retries
+    pub fn backoff_cap(mut self, delay: Duration) -> Self {
+        self.backoff_cap = delay;
+        self
+    }
+
+    /// Set the `User-Agent` header sent with every request
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Add a header sent with every request
+    /// 
+    /// This includes requests to third-party hosts linked from llms.txt; use
+    /// [`HttpClientBuilder::host_header`] for credentials.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Add a header sent only to the hosts matching a pattern
+    pub fn host_header(mut self, hosts: HostPattern, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.host_headers.push((hosts, name.into(), value.into()));
+        self
+    }
+
+    /// Send a bearer token to the hosts matching a pattern
+    pub fn bearer_token(self, hosts: HostPattern, token: impl AsRef<str>) -> Self {
+        let value = format!("Bearer {}", token.as_ref().trim());
+        self.host_header(hosts, "Authorization", value)
+    }
+
+    /// Use the logins of a netrc file for basic authentication
+    /// 
+    /// Each `machine` entry is only sent to that exact host; the `default`
+    /// entry is ignored.
+    pub fn netrc(mut self, path: impl Into<PathBuf>) -> Self {
+        self.netrc = Some(path.into());
+        self
+    }
+
+    /// Send the cookies of a Netscape cookie jar file, e.g. exported from a browser
+    /// 
+    /// Cookies are only sent to the hosts and paths they were set for.
+    pub fn cookie_jar(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cookie_jar = Some(path.into());
+        self
+    }
+
+    /// Limit the request rate and concurrency for each host
+    /// 
+    /// The limits cover every request the client sends, including llms.txt
+    /// itself, and are shared by all clones of the client.
+    pub fn host_limits(mut self, limits: HostLimits) -> Self {
+        self.host_limits = limits;
+        self
+    }
+
+    /// Send all requests through a proxy, e.g. `http://proxy.example.com:3128`
+    /// 
+    /// Overrides the `HTTP_PROXY` / `HTTPS_PROXY` / `ALL_PROXY` environment
+    /// variables, which are used otherwise.
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Bypass the proxy for a comma-separated list of hosts, domains and IP ranges
+    /// 
+    /// The list is added to the `NO_PROXY` environment variable. `*` turns
+    /// off all proxies, including those from the environment.
+    pub fn no_proxy(mut self, hosts: impl Into<String>) -> Self {
+        self.no_proxy = Some(hosts.into());
+        self
+    }
+
+    /// Trust the certificates of a PEM file in addition to the system's
+    /// 
+    /// Needed behind proxies that intercept TLS with their own CA.
+    pub fn ca_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_certs.push(path.into());
+        self
+    }
+
+    /// Authenticate with a client certificate and its PKCS#8 private key, both PEM files
+    pub fn client_cert(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        self.client_cert = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// Accept invalid TLS certificates and host names
+    /// 
+    /// This makes every HTTPS connection open to interception. Only use it
+    /// to diagnose certificate problems.
+    pub fn insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    /// Build the client
+    /// 
+    /// # Returns
+    /// * `Result<HttpClient>` - The client, or an error if a header, the proxy,
+    ///   a certificate or a credentials file is invalid
+    pub fn build(self) -> Result<HttpClient> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|_| DownloadError::InvalidConfig(format!("invalid header name '{name}'")))?;
+            let value = HeaderValue::from_str(value.trim())
+                .map_err(|_| DownloadError::InvalidConfig(format!("invalid value for header '{name}'")))?;
+            headers.append(name, value);
+        }
+        
+        let mut credentials = Credentials::default();
+        for (hosts, name, value) in &self.host_headers {
+            credentials.add_header(hosts.clone(), name, value)?;
+        }
+        if let Some(path) = &self.netrc {
+            credentials.load_netrc(path)?;
+        }
+        if let Some(path) = &self.cookie_jar {
+            credentials.load_cookie_jar(path)?;
+        }
+        let credentials = Arc::new(credentials);
+        
+        let mut client = Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .user_agent(&self.user_agent)
+            .default_headers(headers)
+            .redirect(redirect_policy(Arc::clone(&credentials)))
+            .danger_accept_invalid_certs(self.insecure);
+        
+        for path in &self.ca_certs {
+            let pem = read_config_file(path, "CA certificate")?;
+            let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+                DownloadError::InvalidConfig(format!("invalid CA certificate {}: {e}", path.display()))
+            })?;
+            for cert in certs {
+                client = client.add_root_certificate(cert);
+            }
+        }
+        
+        if let Some((cert_path, key_path)) = &self.client_cert {
+            let cert = read_config_file(cert_path, "client certificate")?;
+            let key = read_config_file(key_path, "client key")?;
+            let identity = Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+                DownloadError::InvalidConfig(format!("invalid client certificate or key: {e}"))
+            })?;
+            client = client.identity(identity);
+        }
+        
+        for proxy in self.proxies()? {
+            client = client.proxy(proxy);
+        }
+        if self.no_proxy.as_deref().is_some_and(|hosts| hosts.trim() == "*") {
+            client = client.no_proxy();
+        }
+        
+        let client = client.build()?;
+        
+        Ok(HttpClient {
+            client,
+            read_timeout: self.read_timeout,
+            file_timeout: self.file_timeout,
+            max_retries: self.max_retries,
+            backoff_base: self.backoff_base,
+            backoff_cap: self.backoff_cap.max(self.backoff_base),
+            events: None,
+            status: None,
+            throttle: Arc::new(HostThrottle::new(self.host_limits)),
+            user_agent: self.user_agent,
+            robots: Arc::default(),
+            credentials,
+        })
+    }
+}
+
+impl HttpClientBuilder {
+    /// Build the proxies to configure explicitly
+    /// 
+    /// Without a bypass list reqwest's own handling of the environment
+    /// variables is used. With one, the proxies from the environment are
+    /// set up here so the list can be applied to them.
+    fn proxies(&self) -> Result<Vec<Proxy>> {
+        let no_proxy = match self.no_proxy.as_deref().map(str::trim) {
+            Some("*") => return Ok(Vec::new()),
+            Some(hosts) => {
+                let env_hosts = std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")).unwrap_or_default();
+                NoProxy::from_string(&format!("{hosts},{env_hosts}"))
+            }
+            None if self.proxy.is_some() => NoProxy::from_env(),
+            None => return Ok(Vec::new()),
+        };
+        
+        let invalid = |e: reqwest::Error| DownloadError::InvalidConfig(format!("invalid proxy: {e}"));
+        let mut proxies = Vec::new();
+        match &self.proxy {
+            Some(proxy) => proxies.push(Proxy::all(proxy).map_err(invalid)?),
+            None => {
+                if let Some(proxy) = env_var(&["HTTP_PROXY", "http_proxy"]) {
+                    proxies.push(Proxy::http(proxy).map_err(invalid)?);
+                }
+                if let Some(proxy) = env_var(&["HTTPS_PROXY", "https_proxy"]) {
+                    proxies.push(Proxy::https(proxy).map_err(invalid)?);
+                }
+                if let Some(proxy) = env_var(&["ALL_PROXY", "all_proxy"]) {
+                    proxies.push(Proxy::all(proxy).map_err(invalid)?);
+                }
+            }
+        }
+        
+        Ok(proxies.into_iter().map(|proxy| proxy.no_proxy(no_proxy.clone())).collect())
+    }
+}
+
+impl Default for HttpClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient {
+    /// Create a new HTTP client with default settings
+    pub fn new() -> Self {
+        Self::builder().build().expect("default HTTP client settings are valid")
+    }
+
+    /// Create a builder for configuring a client
+    pub fn builder() -> HttpClientBuilder {
+        HttpClientBuilder::new()
+    }
+
+    /// Report retries and download progress to an event sink
+    pub fn with_events(mut self, events: Arc<dyn EventSink>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Report retries as human-readable messages
+    /// 
+    /// Only used without an event sink. Retries are not reported at all when
+    /// neither is set.
+    pub fn with_status(mut self, status: StatusCallback) -> Self {
+        self.status = Some(status);
+        self
+    }
+
     /// Fetch text content from a URL with retry logic
     pub async fn fetch_content(&self, url: &str) -> Result<String> {
+        self.fetch_page(url).await.map(|(_, content)| content)
+    }
+
+    /// Fetch text content from a URL with retry logic, along with the response headers
+    pub async fn fetch_page(&self, url: &str) -> Result<(HeaderMap, String)> {
+        let _slot = self.throttle.acquire_slot(url).await;
+        self.with_deadline(url, self.fetch_content_with_retries(url)).await
+    }
+
+    async fn fetch_content_with_retries(&self, url: &str) -> Result<(HeaderMap, String)> {
         let mut last_error = None;
         
         for attempt in 0..=self.max_retries {
-            match self.client.get(url).send().await {
+            let mut retry_after = None;
+            
+            match self.send(self.client.get(url), url).await {
                 Ok(response) => {
                     if response.status().is_success() {
-                        match response.text().await {
-                            Ok(content) => return Ok(content),
+                        let headers = response.headers().clone();
+                        match self.read_text(response, url).await {
+                            Ok(content) => return Ok((headers, content)),
                             Err(e) => {
-                                last_error = Some(DownloadError::NetworkError(e));
+                                last_error = Some(e);
                             }
                         }
                     } else {
                         let status = response.status();
+                        let error = status_error(status, url);
                         
-                        // Create more specific error types
-                        let error = match status.as_u16() {
-                            404 => DownloadError::FileNotFound(url.to_string()),
-                            403 => DownloadError::HttpError { status: 403, url: url.to_string() },
-                            401 => DownloadError::HttpError { status: 401, url: url.to_string() },
-                            500..=599 => DownloadError::HttpError { status: status.as_u16(), url: url.to_string() },
-                            _ => DownloadError::HttpError { status: status.as_u16(), url: url.to_string() },
-                        };
-                        
-                        // Don't retry on client errors (4xx)
-                        if status.is_client_error() {
+                        // Don't retry on client errors (4xx) except rate limiting
+                        if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
                             return Err(error);
                         }
                         
+                        retry_after = self.throttle(url, &response, attempt);
                         last_error = Some(error);
                     }
                 }
+                // A refused redirect fails the same way every time
+                Err(DownloadError::NetworkError(e)) if e.is_redirect() => {
+                    return Err(DownloadError::NetworkError(e));
+                }
                 Err(e) => {
-                    if e.is_timeout() {
-                        last_error = Some(DownloadError::Timeout(url.to_string()));
-                    } else {
-                        last_error = Some(DownloadError::NetworkError(e));
-                    }
+                    last_error = Some(e);
                 }
             }
             
             // Don't sleep after the last attempt
             if attempt < self.max_retries {
-                let delay = self.calculate_delay(attempt);
-                println!("⚠️  Request failed, retrying in {:?}... (attempt {}/{})", 
-                        delay, attempt + 1, self.max_retries);
+                let delay = retry_after.unwrap_or_else(|| self.calculate_delay(attempt));
+                if !self.emit_retry(url, attempt, delay, last_error.as_ref()) {
+                    self.report_status(&format!("⚠️  Request failed, retrying in {:?}... (attempt {}/{})",
+                            delay, attempt + 1, self.max_retries));
+                }
                 sleep(delay).await;
             }
         }
@@ -84,89 +482,167 @@ impl HttpClient {
     /// Download a file from a URL and save it to the specified local path
     /// Returns the number of bytes downloaded
     pub async fn download_file(&self, url: &str, local_path: &Path) -> Result<u64> {
+        match self.download_file_if_modified(url, local_path, None, None).await? {
+            DownloadOutcome::Downloaded { metadata, .. } => Ok(metadata.size),
+            // Without validators the server has no reason to answer 304 Not Modified
+            DownloadOutcome::NotModified => Ok(fs::metadata(local_path).await?.len()),
+        }
+    }
+
+    /// Download a file unless the server reports the cached copy is still current
+    /// 
+    /// When `cached` metadata is given, its ETag and Last-Modified values are sent
+    /// as `If-None-Match` / `If-Modified-Since` headers and a `304 Not Modified`
+    /// response leaves the local file untouched.
+    /// 
+    /// The body is streamed to a `.part` file next to `local_path`, which is
+    /// renamed into place once complete. If a progress bar is given, it is
+    /// switched to byte progress and updated as chunks arrive.
+    /// 
+    /// A `.part` file left behind by a failed attempt or an earlier run is
+    /// resumed with a `Range` request, validated with `If-Range` so a changed
+    /// remote file is fetched in full instead.
+    /// 
+    /// The deadline set with [`HttpClientBuilder::file_timeout`] covers all
+    /// attempts; the partial file is kept when it expires.
+    pub async fn download_file_if_modified(
+        &self,
+        url: &str,
+        local_path: &Path,
+        cached: Option<&FileMetadata>,
+        progress: Option<&ProgressBar>,
+    ) -> Result<DownloadOutcome> {
+        let _slot = self.throttle.acquire_slot(url).await;
+        self.with_deadline(url, self.download_with_retries(url, local_path, cached, progress)).await
+    }
+
+    async fn download_with_retries(
+        &self,
+        url: &str,
+        local_path: &Path,
+        cached: Option<&FileMetadata>,
+        progress: Option<&ProgressBar>,
+    ) -> Result<DownloadOutcome> {
+        let partial_path = partial_file_path(local_path);
         let mut last_error = None;
         
         for attempt in 0..=self.max_retries {
-            match self.client.get(url).send().await {
+            let mut retry_after = None;
+            
+            // Continue where a previous attempt or run left off
+            let resume = resume_point(&partial_path);
+            
+            let mut request = self.client.get(url);
+            match &resume {
+                Some((offset, partial)) => {
+                    request = request.header(RANGE, format!("bytes={offset}-"));
+                    if let Some(validator) = partial.if_range() {
+                        request = request.header(IF_RANGE, validator);
+                    }
+                }
+                None => {
+                    if let Some(cached) = cached {
+                        if let Some(etag) = &cached.etag {
+                            request = request.header(IF_NONE_MATCH, etag);
+                        }
+                        if let Some(last_modified) = &cached.last_modified {
+                            request = request.header(IF_MODIFIED_SINCE, last_modified);
+                        }
+                    }
+                }
+            }
+            
+            match self.send(request, url).await {
                 Ok(response) => {
-                    if response.status().is_success() {
-                        // Get content length for progress bar
-                        let content_length = response.content_length();
+                    let status = response.status();
+                    
+                    if status == StatusCode::NOT_MODIFIED && resume.is_none() {
+                        return Ok(DownloadOutcome::NotModified);
+                    }
+                    
+                    if status.is_success() {
+                        // 206 continues the partial file, anything else is the whole file
+                        let resumed = match &resume {
+                            Some((offset, partial)) if status == StatusCode::PARTIAL_CONTENT => {
+                                if content_range_start(&response) == Some(*offset) {
+                                    Some((*offset, partial.clone()))
+                                } else {
+                                    PartialDownload::discard(&partial_path);
+                                    last_error = Some(DownloadError::ParseError(format!(
+                                        "Server returned an unexpected range for {url}"
+                                    )));
+                                    continue;
+                                }
+                            }
+                            _ => None,
+                        };
                         
-                        // Create progress bar if we know the size
-                        let progress_bar = if let Some(size) = content_length {
-                            let pb = ProgressBar::new(size);
-                            pb.set_style(
-                                ProgressStyle::default_bar()
-                                    .template("      [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
-                                    .unwrap()
-                                    .progress_chars("#>-")
-                            );
-                            Some(pb)
-                        } else {
-                            None
+                        let (offset, previous) = resumed.unwrap_or_default();
+                        let validators = PartialDownload {
+                            etag: header_value(&response, ETAG).or(previous.etag),
+                            last_modified: header_value(&response, LAST_MODIFIED).or(previous.last_modified),
                         };
                         
-                        match response.bytes().await {
-                            Ok(bytes) => {
-                                let bytes_len = bytes.len() as u64;
-                                
-                                // Update progress bar
-                                if let Some(pb) = &progress_bar {
-                                    pb.set_position(bytes_len);
-                                    pb.finish_and_clear();
-                                }
-                                
-                                // Ensure the parent directory exists
-                                if let Some(parent) = local_path.parent() {
-                                    fs::create_dir_all(parent).await?;
-                                }
-                                
-                                // Write the file
-                                fs::write(local_path, bytes).await?;
-                                return Ok(bytes_len);
+                        let content_type = header_value(&response, CONTENT_TYPE);
+                        let status = status.as_u16();
+                        
+                        match self.stream_to_file(url, response, local_path, offset, &validators, progress).await {
+                            Ok((size, sha256)) => {
+                                return Ok(DownloadOutcome::Downloaded {
+                                    metadata: FileMetadata {
+                                        etag: validators.etag,
+                                        last_modified: validators.last_modified,
+                                        size,
+                                        sha256,
+                                        content_type,
+                                    },
+                                    resumed_bytes: offset,
+                                    status,
+                                });
                             }
-                            Err(e) => {
-                                if let Some(pb) = progress_bar {
-                                    pb.abandon_with_message("Download failed");
-                                }
-                                last_error = Some(DownloadError::NetworkError(e));
+                            // Retry when the connection drops or stalls mid-transfer
+                            Err(e @ (DownloadError::NetworkError(_) | DownloadError::Timeout(_))) => {
+                                last_error = Some(e);
                             }
+                            Err(e) => return Err(e),
                         }
+                    } else if status == StatusCode::RANGE_NOT_SATISFIABLE && resume.is_some() {
+                        // The partial file does not match the remote file, start over
+                        PartialDownload::discard(&partial_path);
+                        last_error = Some(status_error(status, url));
+                        continue;
                     } else {
-                        let status = response.status();
+                        let error = status_error(status, url);
                         
-                        // Create more specific error types
-                        let error = match status.as_u16() {
-                            404 => DownloadError::FileNotFound(url.to_string()),
-                            403 => DownloadError::HttpError { status: 403, url: url.to_string() },
-                            401 => DownloadError::HttpError { status: 401, url: url.to_string() },
-                            500..=599 => DownloadError::HttpError { status: status.as_u16(), url: url.to_string() },
-                            _ => DownloadError::HttpError { status: status.as_u16(), url: url.to_string() },
-                        };
-                        
-                        // Don't retry on client errors (4xx)
-                        if status.is_client_error() {
+                        // Don't retry on client errors (4xx) except rate limiting
+                        if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
                             return Err(error);
                         }
                         
+                        retry_after = self.throttle(url, &response, attempt);
                         last_error = Some(error);
                     }
                 }
+                // A refused redirect fails the same way every time
+                Err(DownloadError::NetworkError(e)) if e.is_redirect() => {
+                    return Err(DownloadError::NetworkError(e));
+                }
                 Err(e) => {
-                    if e.is_timeout() {
-                        last_error = Some(DownloadError::Timeout(url.to_string()));
-                    } else {
-                        last_error = Some(DownloadError::NetworkError(e));
-                    }
+                    last_error = Some(e);
                 }
             }
             
             // Don't sleep after the last attempt
             if attempt < self.max_retries {
-                let delay = self.calculate_delay(attempt);
-                println!("      ⚠️  Download failed, retrying in {:?}... (attempt {}/{})", 
+                let delay = retry_after.unwrap_or_else(|| self.calculate_delay(attempt));
+                let message = format!("      ⚠️  Download failed, retrying in {:?}... (attempt {}/{})",
                         delay, attempt + 1, self.max_retries);
+                match progress {
+                    _ if self.emit_retry(url, attempt, delay, last_error.as_ref()) => {}
+                    // Print above the progress bars instead of through them
+                    Some(progress) => progress.println(message),
+                    None => self.report_status(&message),
+                }
                 sleep(delay).await;
             }
         }
@@ -176,10 +652,244 @@ impl HttpClient {
         }))
     }
 
-    /// Calculate exponential backoff delay
+    /// Stream a response body to disk, then move the file into place
+    /// 
+    /// With a non-zero `offset` the body is appended to the existing partial
+    /// file. The partial file is kept when the transfer fails, so it can be
+    /// resumed later.
+    /// 
+    /// Returns the total size of the file and its hex-encoded SHA-256
+    async fn stream_to_file(
+        &self,
+        url: &str,
+        mut response: Response,
+        local_path: &Path,
+        offset: u64,
+        validators: &PartialDownload,
+        progress: Option<&ProgressBar>,
+    ) -> Result<(u64, String)> {
+        // Ensure the parent directory exists
+        if let Some(parent) = local_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        
+        let partial_path = partial_file_path(local_path);
+        let mut hasher = Sha256::new();
+        
+        let mut file = if offset > 0 {
+            // Hash what is already on disk so the digest covers the whole file
+            hash_file(&partial_path, &mut hasher).await?;
+            fs::OpenOptions::new().append(true).open(&partial_path).await?
+        } else {
+            let file = fs::File::create(&partial_path).await?;
+            // Without a validator a partial file cannot be resumed safely
+            if validators.if_range().is_some() {
+                validators.save(&partial_path)?;
+            } else {
+                PartialDownload::forget(&partial_path);
+            }
+            file
+        };
+        
+        let total = response.content_length().map(|length| length + offset);
+        if let Some(progress) = progress {
+            start_byte_progress(progress, total, offset);
+        }
+        
+        let mut written = offset;
+        let mut last_event = Instant::now();
+        let streamed = async {
+            while let Some(chunk) = self.read_chunk(&mut response, url).await? {
+                let chunk = chunk.as_ref();
+                file.write_all(chunk).await?;
+                hasher.update(chunk);
+                written += chunk.len() as u64;
+                
+                if let Some(progress) = progress {
+                    progress.set_position(written);
+                }
+                if last_event.elapsed() >= PROGRESS_EVENT_INTERVAL {
+                    last_event = Instant::now();
+                    self.emit(DownloadEvent::Progress { url: url.to_string(), bytes: written, total });
+                }
+            }
+            self.emit(DownloadEvent::Progress { url: url.to_string(), bytes: written, total });
+            
+            // Make sure the data is on disk before the file becomes visible
+            file.sync_all().await?;
+            Ok::<_, DownloadError>(())
+        }
+        .await;
+        drop(file);
+        streamed?;
+        
+        fs::rename(&partial_path, local_path).await?;
+        PartialDownload::forget(&partial_path);
+        Ok((written, to_hex(&hasher.finalize())))
+    }
+
+    /// Check that a URL is reachable without downloading its body
+    /// 
+    /// Sends a HEAD request, falling back to GET for servers that do not
+    /// support HEAD. No retries are attempted.
+    pub async fn check_url(&self, url: &str) -> Result<()> {
+        let _slot = self.throttle.acquire_slot(url).await;
+        let mut response = self.send(self.client.head(url), url).await?;
+        
+        if matches!(response.status().as_u16(), 405 | 501) {
+            response = self.send(self.client.get(url), url).await?;
+        }
+        
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(status_error(status, url))
+        }
+    }
+
+    /// Send a request, waiting at most the read timeout for the response headers
+    /// 
+    /// Waits first until the per-host limits allow another request, then adds
+    /// the credentials for the host.
+    async fn send(&self, request: RequestBuilder, url: &str) -> Result<Response> {
+        self.throttle.wait_turn(url).await;
+        let request = self.credentials.apply(request, url);
+        
+        match timeout(self.read_timeout, request.send()).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) if e.is_timeout() => Err(DownloadError::Timeout(url.to_string())),
+            Ok(Err(e)) => Err(DownloadError::NetworkError(e)),
+            Err(_) => Err(DownloadError::Timeout(url.to_string())),
+        }
+    }
+
+    /// Read the next chunk of a response body, waiting at most the read timeout
+    async fn read_chunk(&self, response: &mut Response, url: &str) -> Result<Option<impl AsRef<[u8]>>> {
+        match timeout(self.read_timeout, response.chunk()).await {
+            Ok(chunk) => Ok(chunk?),
+            Err(_) => Err(DownloadError::Timeout(url.to_string())),
+        }
+    }
+
+    /// Read a whole response body as text
+    async fn read_text(&self, mut response: Response, url: &str) -> Result<String> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.read_chunk(&mut response, url).await? {
+            body.extend_from_slice(chunk.as_ref());
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Hold back every request to a host after a 429 or 503 response
+    /// 
+    /// The host is paused for the `Retry-After` delay, or the regular backoff
+    /// delay when the server gives none, so all tasks back off together.
+    /// Returns the delay, or None for other responses.
+    fn throttle(&self, url: &str, response: &Response, attempt: u32) -> Option<Duration> {
+        let status = response.status();
+        if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+            return None;
+        }
+        
+        let delay = match retry_after(response) {
+            Some(delay) => delay.min(MAX_RETRY_AFTER),
+            // Plain 503s are usually transient and not a reason to stop the whole host
+            None if status == StatusCode::SERVICE_UNAVAILABLE => return None,
+            None => self.calculate_delay(attempt),
+        };
+        
+        self.throttle.pause(url, delay);
+        Some(delay)
+    }
+
+    /// Run a request, giving up once the per-file deadline has passed
+    async fn with_deadline<T>(&self, url: &str, request: impl Future<Output = Result<T>>) -> Result<T> {
+        match self.file_timeout {
+            Some(deadline) => timeout(deadline, request)
+                .await
+                .unwrap_or_else(|_| Err(DownloadError::Timeout(url.to_string()))),
+            None => request.await,
+        }
+    }
+
+    /// Send an event to the event sink, if there is one
+    fn emit(&self, event: DownloadEvent) {
+        if let Some(events) = &self.events {
+            events.emit(&event);
+        }
+    }
+
+    /// Report a retry as an event, returns whether an event sink took it
+    fn emit_retry(&self, url: &str, attempt: u32, delay: Duration, error: Option<&DownloadError>) -> bool {
+        self.emit(DownloadEvent::Retry {
+            url: url.to_string(),
+            attempt: attempt + 1,
+            max_retries: self.max_retries,
+            delay_ms: delay.as_millis() as u64,
+            error: error.map(ToString::to_string).unwrap_or_default(),
+        });
+        self.events.is_some()
+    }
+
+    /// Pass a human-readable message to the status callback, if there is one
+    fn report_status(&self, message: &str) {
+        if let Some(status) = &self.status {
+            status(message);
+        }
+    }
+
+    /// Check if robots.txt of a URL's host allows fetching the URL
+    /// 
+    /// robots.txt is fetched once per origin and matched against the
+    /// client's User-Agent. A missing robots.txt (any 4xx status) allows
+    /// everything, while one that cannot be fetched because of a server or
+    /// network error disallows everything. A `Crawl-delay` becomes the
+    /// minimum delay between requests to the host.
+    pub async fn allowed_by_robots(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else {
+            return false;
+        };
+        let origin = parsed.origin().ascii_serialization();
+        
+        let cached = self.robots.lock().unwrap().get(&origin).cloned();
+        let robots = match cached {
+            Some(robots) => robots,
+            None => {
+                let robots = Arc::new(self.fetch_robots(&origin).await);
+                if let Some(delay) = robots.crawl_delay() {
+                    self.throttle.set_crawl_delay(url, delay);
+                }
+                self.robots.lock().unwrap().insert(origin, Arc::clone(&robots));
+                robots
+            }
+        };
+        
+        let mut path = parsed.path().to_string();
+        if let Some(query) = parsed.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        robots.is_allowed(&path)
+    }
+
+    /// Fetch and parse the robots.txt of an origin
+    async fn fetch_robots(&self, origin: &str) -> RobotsTxt {
+        match self.fetch_content(&format!("{origin}/robots.txt")).await {
+            Ok(content) => RobotsTxt::parse(&content, &self.user_agent),
+            Err(DownloadError::FileNotFound(_)) => RobotsTxt::default(),
+            Err(DownloadError::HttpError { status: 400..=499, .. }) => RobotsTxt::default(),
+            Err(_) => RobotsTxt::disallow_all(),
+        }
+    }
+
+    /// Calculate exponential backoff delay with full jitter
     fn calculate_delay(&self, attempt: u32) -> Duration {
-        let multiplier = 2_u64.pow(attempt);
-        Duration::from_millis(self.base_delay.as_millis() as u64 * multiplier)
+        let ceiling = self
+            .backoff_base
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.backoff_cap);
+        random_duration(ceiling)
     }
 }
 
@@ -189,3 +899,183 @@ impl Default for HttpClient {
     }
 }
 
+/// Read a file named in the client settings
+fn read_config_file(path: &Path, what: &str) -> Result<Vec<u8>> {
+    std::fs::read(path)
+        .map_err(|e| DownloadError::InvalidConfig(format!("could not read {what} {}: {e}", path.display())))
+}
+
+/// Follow redirects, except to hosts outside the scope of host-scoped headers
+/// 
+/// reqwest drops `Authorization` and `Cookie` headers on redirects to another
+/// host, but keeps all others, which would leak e.g. an API key header.
+fn redirect_policy(credentials: Arc<Credentials>) -> Policy {
+    Policy::custom(move |attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        
+        // The headers were chosen for the URL of the original request
+        let Some(original) = attempt.previous().first() else {
+            return attempt.follow();
+        };
+        if credentials.leaks_on_redirect(original, attempt.url()) {
+            let message = format!(
+                "refusing to follow a redirect from {} to {} with host-scoped headers",
+                original.origin().ascii_serialization(),
+                attempt.url().origin().ascii_serialization()
+            );
+            return attempt.error(message);
+        }
+        attempt.follow()
+    })
+}
+
+/// Get the first non-empty environment variable of a list
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Pick a random duration between zero and `max`
+/// 
+/// Every `RandomState` is seeded with fresh random keys, which is plenty for
+/// spreading out retries.
+fn random_duration(max: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let nanos = max.as_nanos().min(u64::MAX as u128) as u64;
+    Duration::from_nanos(random % nanos.saturating_add(1))
+}
+
+/// Create more specific error types for unsuccessful HTTP status codes
+fn status_error(status: StatusCode, url: &str) -> DownloadError {
+    match status.as_u16() {
+        404 => DownloadError::FileNotFound(url.to_string()),
+        code => DownloadError::HttpError { status: code, url: url.to_string() },
+    }
+}
+
+/// Parse a `Retry-After` header, given either in seconds or as an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    parse_retry_after(&header_value(response, RETRY_AFTER)?, SystemTime::now())
+}
+
+/// Parse a `Retry-After` value, relative to `now` when it is an HTTP date
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    
+    let date = httpdate::parse_http_date(value).ok()?;
+    // A date in the past means the request can be retried right away
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Read a response header as a string, if present and valid
+fn header_value(response: &Response, name: HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Find where a previous attempt left off
+/// 
+/// Returns the size of the partial file and its validators, or None if there
+/// is nothing that can be resumed. Unusable partial files are removed.
+fn resume_point(partial_path: &Path) -> Option<(u64, PartialDownload)> {
+    let size = std::fs::metadata(partial_path).ok()?.len();
+    
+    match PartialDownload::load(partial_path) {
+        Some(partial) if size > 0 && partial.if_range().is_some() => Some((size, partial)),
+        _ => {
+            PartialDownload::discard(partial_path);
+            None
+        }
+    }
+}
+
+/// Parse the first byte position from a `Content-Range: bytes N-M/T` header
+fn content_range_start(response: &Response) -> Option<u64> {
+    header_value(response, CONTENT_RANGE)?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Feed the content of a file into a hasher
+async fn hash_file(path: &Path, hasher: &mut Sha256) -> Result<()> {
+    let mut file = fs::File::open(path).await?;
+    let mut buffer = vec![0; 64 * 1024];
+    
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+/// Switch a per-file progress bar to byte progress for a new transfer
+fn start_byte_progress(progress: &ProgressBar, content_length: Option<u64>, position: u64) {
+    let template = match content_length {
+        Some(size) => {
+            progress.set_length(size);
+            "{spinner:.blue} [{elapsed_precise}] {msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})"
+        }
+        None => "{spinner:.blue} [{elapsed_precise}] {msg} {bytes} ({bytes_per_sec})",
+    };
+    
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("#>-")
+    );
+    progress.set_position(position);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sun, 06 Nov 1994 08:49:37 GMT, the example date of RFC 9110
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777)
+    }
+
+    #[test]
+    fn retry_after_in_seconds() {
+        assert_eq!(parse_retry_after("120", now()), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 ", now()), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn retry_after_as_http_date() {
+        assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:51:37 GMT", now()), Some(Duration::from_secs(120)));
+        // The obsolete RFC 850 and asctime formats are accepted as well
+        assert_eq!(parse_retry_after("Sunday, 06-Nov-94 08:50:37 GMT", now()), Some(Duration::from_secs(60)));
+        assert_eq!(parse_retry_after("Sun Nov  6 08:49:47 1994", now()), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn retry_after_in_the_past_is_zero() {
+        assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now()), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn invalid_retry_after_is_ignored() {
+        for value in ["", "soon", "-5", "1.5", "Sun, 06 Nov 1994"] {
+            assert_eq!(parse_retry_after(value, now()), None, "{value}");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Download documentation files from websites that implement the
+//! [llms.txt standard](https://llmstxt.org/).
+//!
+//! The [`Downloader`] fetches a website's `llms.txt` (or `llms-full.txt`),
+//! parses it into an [`LlmsTxtDocument`] and downloads every listed file
+//! into a per-domain directory, preserving the original directory structure.
+//!
+//! ```no_run
+//! use llmsdl::{Downloader, SectionFilter};
+//!
+//! # async fn run() -> Result<(), llmsdl::DownloadError> {
+//! let downloader = Downloader::builder()
+//!     .output_dir("downloads")
+//!     .max_concurrent(8)
+//!     .filter(SectionFilter {
+//!         skip_optional: true,
+//!         ..SectionFilter::default()
+//!     })
+//!     .build();
+//!
+//! let result = downloader.download("https://docs.example.com").await?;
+//! println!("Downloaded {} files", result.success_count());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The lower-level building blocks are available as well: [`HttpClient`] for
+//! fetching with retries, [`parse_llms_txt`] for parsing and
+//! [`validate_llms_txt`] for checking a file against the spec.
+
+#![warn(missing_docs)]
+
+/// Host-scoped credentials: headers, bearer tokens, netrc and cookies
+pub mod auth;
+/// Per-domain metadata cache for conditional re-downloads
+pub mod cache;
+/// Finding llms.txt at alternate locations and subpaths
+pub mod discovery;
+/// Download orchestration and the [`Downloader`] builder
+pub mod downloader;
+/// Error types and download result tracking
+pub mod error;
+/// Per-file events for machine-readable progress output
+pub mod events;
+/// Mapping URLs to local files and directories
+pub mod file_manager;
+/// HTTP client with retry logic
+pub mod http_client;
+/// Download manifest written to each domain directory
+pub mod manifest;
+/// llms.txt and llms-full.txt parsing
+pub mod parser;
+/// End-of-run reports in JSON, JUnit XML and Markdown
+pub mod report;
+/// robots.txt parsing for --respect-robots
+pub mod robots;
+/// Comparing runs with the previous manifest and pruning removed files
+pub mod sync;
+/// Per-host rate limits and concurrency caps
+pub mod throttle;
+/// llms.txt linting for the validate subcommand
+pub mod validator;
+
+pub use auth::HostPattern;
+pub use cache::{DownloadCache, FileMetadata};
+pub use discovery::{candidates, discover, Discovered, IndexFile};
+pub use downloader::{validate_url, Downloader, DownloaderBuilder, SourceMode};
+pub use error::{
+    BatchResult, DownloadError, DownloadFailure, DownloadResult, ErrorKind, PlannedFile, SiteResult, SkippedFile,
+};
+pub use events::{DownloadEvent, EventSink, FinishOutcome, NdjsonWriter};
+pub use file_manager::{CrossOriginPolicy, MappingManifest, PathMapper};
+pub use http_client::{DownloadOutcome, HttpClient, HttpClientBuilder, StatusCallback};
+pub use manifest::{Manifest, ManifestFile, ManifestSource};
+pub use parser::{
+    parse_llms_txt, split_llms_full, FullTextDocument, LlmsTxtDocument, LlmsTxtEntry, LlmsTxtSection,
+    SectionFilter,
+};
+pub use report::{render_batch_report, render_report, ReportFormat};
+pub use robots::RobotsTxt;
+pub use sync::{ChangedFile, Changes, PruneMode};
+pub use throttle::{HostLimits, Rate};
+pub use validator::{validate_llms_txt, LintIssue, LintReport, Severity};
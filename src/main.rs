@@ -1,253 +1,1451 @@
 // Entry point and CLI setup
 
-use clap::Parser;
+mod config;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 use url::Url;
-use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
-use futures::future::join_all;
+
+use config::{Config, Defaults, SiteProfile};
+use llmsdl::{
+    discover, render_batch_report, render_report, validate_llms_txt, validate_url, BatchResult, Changes,
+    CrossOriginPolicy, Discovered, DownloadError, Downloader, DownloaderBuilder, ErrorKind, HostLimits, HostPattern,
+    HttpClient, IndexFile, NdjsonWriter, PlannedFile, PruneMode, Rate, ReportFormat, SectionFilter, SiteResult,
+    SourceMode,
+};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::process::ExitCode;
 use std::sync::Arc;
-use tokio::sync::Semaphore;
 
-mod error;
-mod http_client;
-mod parser;
-mod file_manager;
+/// Default number of concurrent downloads across all websites
+const DEFAULT_THREADS: usize = 5;
 
-use error::{DownloadError, DownloadResult};
-use http_client::HttpClient;
-use parser::parse_llms_txt;
-use file_manager::{create_local_directory, get_local_file_path};
+/// Default number of websites processed at the same time
+const DEFAULT_PARALLEL_SITES: usize = 4;
 
 /// A simple CLI tool to download documentation files from websites that implement the llms.txt standard
 #[derive(Parser, Debug)]
 #[command(name = "llmstxtdl")]
 #[command(about = "Downloads documentation files from llms.txt enabled websites")]
 #[command(version)]
-struct Args {
-    /// The base URL of the website to check for llms.txt
-    #[arg(help = "Website URL (e.g., https://example.com)")]
-    url: String,
+#[command(after_long_help = EXIT_CODES_HELP)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    
+    #[command(flatten)]
+    download: DownloadArgs,
+    
+    /// Config file with defaults and site profiles
+    #[arg(global = true, long = "config", value_name = "PATH", help = "Config file (default: ./llmsdl.toml, then ~/.config/llmsdl/llmsdl.toml)")]
+    config: Option<PathBuf>,
+    
+    #[command(flatten)]
+    http: HttpArgs,
+}
+
+/// Subcommands besides the default download
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check an llms.txt file against the llms.txt spec
+    Validate(ValidateArgs),
+    /// Download the websites of site profiles in llmsdl.toml
+    Sync(SyncArgs),
+    /// Show which files would be downloaded and where, without downloading them
+    #[command(
+        mut_arg("report", |arg| arg.hide(true)),
+        mut_arg("no_report", |arg| arg.hide(true)),
+        mut_arg("report_file", |arg| arg.hide(true)),
+        mut_arg("events", |arg| arg.hide(true)),
+        mut_arg("events_file", |arg| arg.hide(true)),
+        mut_arg("prune", |arg| arg.hide(true)),
+        mut_arg("fail_on", |arg| arg.hide(true))
+    )]
+    List(ListArgs),
+}
+
+/// Websites to download without a subcommand
+#[derive(Args, Debug)]
+struct DownloadArgs {
+    /// The base URLs of the websites to check for llms.txt
+    #[arg(value_name = "URL", required_unless_present = "from_file", help = "Website URLs (e.g., https://example.com)")]
+    urls: Vec<String>,
+    
+    /// File listing more website URLs, one per line
+    #[arg(long = "from-file", value_name = "PATH", help = "Read website URLs from a file, one per line ('-' for stdin)")]
+    from_file: Option<PathBuf>,
+    
+    #[command(flatten)]
+    options: DownloadOptions,
+}
+
+/// Options for the list subcommand
+#[derive(Args, Debug)]
+struct ListArgs {
+    #[command(flatten)]
+    sites: DownloadArgs,
+    
+    /// Output format for the list
+    #[arg(long = "format", value_enum, default_value = "text", help = "Output format: a table (text) or the JSON report")]
+    format: OutputFormat,
+}
+
+impl ListArgs {
+    /// Rejects the download options that have no effect on a listing
+    fn check(&self) -> Result<(), DownloadError> {
+        let options = &self.sites.options;
+        let given = [
+            ("--report", options.report.is_some() || options.no_report),
+            ("--report-file", options.report_file.is_some()),
+            ("--events", options.events.is_some()),
+            ("--events-file", options.events_file.is_some()),
+            ("--prune", options.prune.is_some()),
+            ("--fail-on", options.fail_on.is_some()),
+        ];
+        match given.into_iter().find(|(_, given)| *given) {
+            Some((flag, _)) => Err(DownloadError::InvalidConfig(format!(
+                "{flag} cannot be used with list, which downloads nothing; use --format json for a report"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Options for the sync subcommand
+#[derive(Args, Debug)]
+struct SyncArgs {
+    /// Names of the site profiles to download
+    #[arg(value_name = "PROFILE", required_unless_present = "all", help = "Site profiles to download")]
+    profiles: Vec<String>,
+    
+    /// Download the websites of all site profiles
+    #[arg(long = "all", conflicts_with = "profiles", help = "Download all site profiles")]
+    all: bool,
     
+    #[command(flatten)]
+    options: DownloadOptions,
+}
+
+/// Options for downloading the files listed in a website's llms.txt
+/// 
+/// Options left out fall back to the `[defaults]` of llmsdl.toml and the
+/// site profile.
+#[derive(Args, Debug)]
+struct DownloadOptions {
     /// Output directory for downloaded documentation files
     #[arg(short = 'o', long = "output", help = "Output directory for downloaded files")]
-    output: String,
+    output: Option<PathBuf>,
     
-    /// Number of concurrent download threads
-    #[arg(short = 't', long = "threads", default_value = "5", help = "Number of concurrent download threads")]
-    threads: usize,
+    /// Number of concurrent downloads across all websites
+    #[arg(short = 't', long = "threads", help = "Number of concurrent download threads (default: 5)")]
+    threads: Option<usize>,
+    
+    /// Number of concurrent downloads from a single website
+    #[arg(long = "site-threads", value_name = "N", help = "Maximum concurrent downloads per website (default: --threads)")]
+    site_threads: Option<usize>,
+    
+    /// Number of websites processed at the same time
+    #[arg(long = "parallel-sites", value_name = "N", help = "Number of websites processed at the same time (default: 4)")]
+    parallel_sites: Option<usize>,
+    
+    /// Skip the "Optional" section defined by the llms.txt spec
+    #[arg(long = "skip-optional", overrides_with = "no_skip_optional", help = "Skip files listed in the \"Optional\" section")]
+    skip_optional: bool,
+    
+    /// Download the "Optional" section even if the site profile skips it
+    #[arg(long = "no-skip-optional", overrides_with = "skip_optional", help = "Download the \"Optional\" section, overriding skip-optional in the site profile")]
+    no_skip_optional: bool,
+    
+    /// Only download files from the named sections
+    #[arg(long = "section", value_name = "NAME", help = "Only download files from this section (repeatable)")]
+    sections: Vec<String>,
+    
+    /// Do not download files from the named sections
+    #[arg(long = "exclude-section", value_name = "NAME", help = "Skip files from this section (repeatable)")]
+    exclude_sections: Vec<String>,
+    
+    /// Fetch llms-full.txt instead of llms.txt
+    #[arg(long = "full", conflicts_with = "prefer_full", help = "Fetch llms-full.txt instead of llms.txt")]
+    full: bool,
+    
+    /// Fetch llms-full.txt when the site publishes one, llms.txt otherwise
+    #[arg(long = "prefer-full", help = "Use llms-full.txt when available, falling back to llms.txt")]
+    prefer_full: bool,
+    
+    /// Split llms-full.txt back into one file per document
+    #[arg(long = "split", overrides_with = "no_split", help = "Split llms-full.txt into per-document files (with --full or --prefer-full)")]
+    split: bool,
+    
+    /// Save llms-full.txt as one file even if the site profile splits it
+    #[arg(long = "no-split", overrides_with = "split", help = "Save llms-full.txt as one file, overriding split in the site profile")]
+    no_split: bool,
+    
+    /// Re-download every file, ignoring ETag / Last-Modified values from previous runs
+    #[arg(long = "no-cache", overrides_with = "cache", help = "Re-download files even if they have not changed")]
+    no_cache: bool,
+    
+    /// Use the download cache even if the config file turns it off
+    #[arg(long = "cache", overrides_with = "no_cache", help = "Skip unchanged files, overriding no-cache in the config")]
+    cache: bool,
+    
+    /// How to handle llms.txt entries hosted on other hosts
+    #[arg(long = "cross-origin", value_enum, help = "Files on other hosts: save under _external/<host>/ or reject them (default: host-dir)")]
+    cross_origin: Option<CrossOrigin>,
+    
+    /// Skip files that robots.txt disallows for our User-Agent
+    #[arg(long = "respect-robots", overrides_with = "no_respect_robots", help = "Honor robots.txt Allow/Disallow rules and Crawl-delay")]
+    respect_robots: bool,
+    
+    /// Ignore robots.txt even if the config file turns it on
+    #[arg(long = "no-respect-robots", overrides_with = "respect_robots", help = "Ignore robots.txt, overriding respect-robots in the config")]
+    no_respect_robots: bool,
+    
+    /// What to do with files of the previous run that are no longer listed
+    #[arg(long = "prune", value_enum, help = "Delete files no longer listed in llms.txt, or move them to .llmsdl-removed/ (quarantine)")]
+    prune: Option<Prune>,
+    
+    /// Compare with the previous run without downloading or changing anything
+    #[arg(long = "dry-run", help = "Show which files would be added and removed without downloading or changing anything")]
+    dry_run: bool,
+    
+    /// Print an end-of-run report in a machine-readable format
+    #[arg(long = "report", value_enum, overrides_with = "no_report", help = "Print an end-of-run report (json, junit or markdown)")]
+    report: Option<ReportKind>,
+    
+    /// Show the human output even if the config file asks for a report
+    #[arg(long = "no-report", overrides_with = "report", help = "Do not print a report, overriding report in the config")]
+    no_report: bool,
+    
+    /// Write the report to a file instead of stdout
+    #[arg(long = "report-file", value_name = "PATH", help = "Write the report to a file instead of stdout")]
+    report_file: Option<PathBuf>,
+    
+    /// Stream per-file events while downloading
+    #[arg(long = "events", value_enum, help = "Stream per-file start/progress/retry/finish events (ndjson)")]
+    events: Option<EventsFormat>,
+    
+    /// Write events to a file instead of stdout
+    #[arg(long = "events-file", value_name = "PATH", requires = "events", help = "Write events to a file instead of stdout")]
+    events_file: Option<PathBuf>,
+    
+    /// Which download outcomes make the process exit with a non-zero code
+    #[arg(long = "fail-on", value_enum, help = "Exit non-zero when any file fails (any, default), only when no file could be downloaded (all-failed), or never (none)")]
+    fail_on: Option<FailOn>,
 }
 
-#[tokio::main]
-async fn main() -> Result<(), DownloadError> {
-    let args = Args::parse();
+/// HTTP settings shared by all commands
+#[derive(Args, Debug)]
+#[command(next_help_heading = "HTTP options")]
+struct HttpArgs {
+    /// How long to wait for a connection to be established
+    #[arg(global = true, long = "connect-timeout", value_name = "DURATION", value_parser = humantime::parse_duration, help = "Connection timeout (default: 10s)")]
+    connect_timeout: Option<Duration>,
     
-    // Validate the URL format
-    let validated_url = validate_url(&args.url)?;
+    /// How long to wait for the response or the next chunk of data
+    #[arg(global = true, long = "read-timeout", value_name = "DURATION", value_parser = humantime::parse_duration, help = "Timeout while waiting for data (default: 30s)")]
+    read_timeout: Option<Duration>,
     
-    println!("🔍 Processing URL: {validated_url}");
+    /// Deadline for fetching a single file, including retries
+    #[arg(global = true, long = "file-timeout", value_name = "DURATION", value_parser = humantime::parse_duration, help = "Give up on a file after this long, e.g. 5m")]
+    file_timeout: Option<Duration>,
     
-    // Process the URL and download files
-    match process_url(&validated_url, &args.output, args.threads).await {
-        Ok(result) => {
-            // Display final summary
-            println!("\n✅ Process completed!");
-            println!("{result}");
-            
-            if result.all_successful() {
-                println!("🎉 All files downloaded successfully!");
-            } else if result.success_count() > 0 {
-                println!("⚠️  Some files failed to download, but {} files were successful.", result.success_count());
-            } else {
-                println!("❌ No files were downloaded successfully.");
-                std::process::exit(1);
+    /// How often a failed request is retried
+    #[arg(global = true, long = "retries", value_name = "N", help = "Number of retries for failed requests (default: 3)")]
+    retries: Option<u32>,
+    
+    /// Base delay of the exponential backoff between retries
+    #[arg(global = true, long = "backoff-base", value_name = "DURATION", value_parser = humantime::parse_duration, help = "Base delay between retries, doubled on every retry (default: 500ms)")]
+    backoff_base: Option<Duration>,
+    
+    /// Longest delay between two retries
+    #[arg(global = true, long = "backoff-cap", value_name = "DURATION", value_parser = humantime::parse_duration, help = "Longest delay between retries (default: 30s)")]
+    backoff_cap: Option<Duration>,
+    
+    /// User-Agent header sent with every request
+    #[arg(global = true, long = "user-agent", value_name = "STRING", help = "User-Agent header (default: llmsdl/<version>)")]
+    user_agent: Option<String>,
+    
+    /// Extra headers sent with every request, or only to the hosts matching a pattern
+    #[arg(global = true, long = "header", value_name = "[HOSTS:] NAME: VALUE", help = "Extra request header, e.g. 'X-Trace: 1' or 'docs.example.com: X-Api-Key: secret' (repeatable)")]
+    headers: Vec<HeaderArg>,
+    
+    /// Bearer tokens read from environment variables
+    #[arg(global = true, long = "bearer-token-env", value_name = "[HOSTS=]VAR", help = "Send the token in this environment variable as a bearer token to the site's host or HOSTS (repeatable)")]
+    bearer_token_envs: Vec<BearerTokenEnv>,
+    
+    /// Basic authentication with the logins of ~/.netrc
+    #[arg(global = true, long = "netrc", conflicts_with = "netrc_file", overrides_with = "no_netrc", help = "Use logins from $NETRC or ~/.netrc for matching hosts")]
+    netrc: bool,
+    
+    /// No basic authentication from netrc files, even if the config file sets one up
+    #[arg(global = true, long = "no-netrc", conflicts_with = "netrc_file", overrides_with = "netrc", help = "Do not use netrc logins, overriding netrc and netrc-file in the config")]
+    no_netrc: bool,
+    
+    /// Basic authentication with the logins of a netrc file
+    #[arg(global = true, long = "netrc-file", value_name = "PATH", help = "Use logins from this netrc file for matching hosts")]
+    netrc_file: Option<PathBuf>,
+    
+    /// Cookies to send, in the Netscape cookies.txt format
+    #[arg(global = true, long = "cookie-jar", value_name = "PATH", help = "Send matching cookies from this Netscape cookie jar file")]
+    cookie_jar: Option<PathBuf>,
+    
+    /// Maximum request rate per host
+    #[arg(global = true, long = "rate", value_name = "RATE", help = "Maximum requests per host, e.g. 5/s or 100/m")]
+    rate: Option<Rate>,
+    
+    /// Maximum number of files fetched from one host at the same time
+    #[arg(global = true, long = "host-concurrency", value_name = "N", help = "Maximum concurrent downloads per host")]
+    host_concurrency: Option<usize>,
+    
+    /// Minimum time between two requests to the same host
+    #[arg(global = true, long = "min-delay", value_name = "DURATION", value_parser = humantime::parse_duration, help = "Minimum delay between requests to the same host, e.g. 200ms")]
+    min_delay: Option<Duration>,
+    
+    /// Proxy for all requests, overriding HTTP_PROXY / HTTPS_PROXY
+    #[arg(global = true, long = "proxy", value_name = "URL", help = "Send all requests through this proxy, e.g. http://proxy:3128")]
+    proxy: Option<String>,
+    
+    /// Hosts that bypass the proxy, or `*` to use no proxy at all
+    #[arg(global = true, long = "no-proxy", value_name = "HOSTS", help = "Comma-separated hosts that bypass the proxy, or '*' for no proxy")]
+    no_proxy: Option<String>,
+    
+    /// Extra CA certificates to trust, e.g. of a TLS-intercepting proxy
+    #[arg(global = true, long = "ca-cert", value_name = "PEM", help = "Trust the CA certificates in this PEM file (repeatable)")]
+    ca_certs: Vec<PathBuf>,
+    
+    /// Client certificate for TLS client authentication
+    #[arg(global = true, long = "client-cert", value_name = "PEM", requires = "client_key", help = "Client certificate (PEM) for TLS client authentication")]
+    client_cert: Option<PathBuf>,
+    
+    /// Private key of the client certificate
+    #[arg(global = true, long = "client-key", value_name = "PEM", requires = "client_cert", help = "PKCS#8 private key (PEM) of the client certificate")]
+    client_key: Option<PathBuf>,
+    
+    /// Accept invalid TLS certificates
+    #[arg(global = true, long = "insecure", overrides_with = "no_insecure", help = "Do not verify TLS certificates (dangerous)")]
+    insecure: bool,
+    
+    /// Verify TLS certificates even if the config file turns verification off
+    #[arg(global = true, long = "no-insecure", overrides_with = "insecure", help = "Verify TLS certificates, overriding insecure in the config")]
+    no_insecure: bool,
+}
+
+impl HttpArgs {
+    /// Fills in the settings left out on the command line from the config file
+    /// 
+    /// Headers, bearer tokens and CA certificates of the config are used in
+    /// addition to those given on the command line.
+    fn apply_config(&mut self, defaults: &Defaults) {
+        self.connect_timeout = self.connect_timeout.or(defaults.connect_timeout);
+        self.read_timeout = self.read_timeout.or(defaults.read_timeout);
+        self.file_timeout = self.file_timeout.or(defaults.file_timeout);
+        self.retries = self.retries.or(defaults.retries);
+        self.backoff_base = self.backoff_base.or(defaults.backoff_base);
+        self.backoff_cap = self.backoff_cap.or(defaults.backoff_cap);
+        self.user_agent = self.user_agent.take().or_else(|| defaults.user_agent.clone());
+        self.headers = defaults.header.iter().cloned().chain(self.headers.drain(..)).collect();
+        self.bearer_token_envs = defaults.bearer_token_env.iter().cloned().chain(self.bearer_token_envs.drain(..)).collect();
+        self.netrc = config_flag(self.netrc, self.no_netrc, defaults.netrc);
+        if !self.no_netrc {
+            self.netrc_file = self.netrc_file.take().or_else(|| defaults.netrc_file.clone());
+        }
+        self.cookie_jar = self.cookie_jar.take().or_else(|| defaults.cookie_jar.clone());
+        self.rate = self.rate.or(defaults.rate);
+        self.host_concurrency = self.host_concurrency.or(defaults.host_concurrency);
+        self.min_delay = self.min_delay.or(defaults.min_delay);
+        self.proxy = self.proxy.take().or_else(|| defaults.proxy.clone());
+        self.no_proxy = self.no_proxy.take().or_else(|| defaults.no_proxy.clone());
+        self.ca_certs = defaults.ca_cert.iter().cloned().chain(self.ca_certs.drain(..)).collect();
+        // The certificate and its key belong together
+        if self.client_cert.is_none() {
+            self.client_cert = defaults.client_cert.clone();
+            self.client_key = defaults.client_key.clone();
+        }
+        self.insecure = config_flag(self.insecure, self.no_insecure, defaults.insecure);
+    }
+
+    /// Builds the HTTP client from the command line settings
+    /// 
+    /// Bearer tokens without a host pattern are sent to the hosts of the
+    /// sites, and so are the headers and bearer tokens of site profiles.
+    /// Without a site host, e.g. for a local file, such a token is not used
+    /// and a warning is printed.
+    fn client(&self, sites: &[Site]) -> Result<HttpClient, DownloadError> {
+        let mut builder = HttpClient::builder()
+            .file_timeout(self.file_timeout)
+            .host_limits(HostLimits {
+                rate: self.rate,
+                max_concurrent: self.host_concurrency,
+                min_delay: self.min_delay,
+            });
+        
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(retries) = self.retries {
+            builder = builder.max_retries(retries);
+        }
+        if let Some(delay) = self.backoff_base {
+            builder = builder.backoff_base(delay);
+        }
+        if let Some(delay) = self.backoff_cap {
+            builder = builder.backoff_cap(delay);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        for header in &self.headers {
+            builder = match &header.hosts {
+                Some(hosts) => builder.host_header(hosts.clone(), &header.name, &header.value),
+                None => builder.header(&header.name, &header.value),
+            };
+        }
+        for bearer in &self.bearer_token_envs {
+            let token = bearer.token()?;
+            let hosts = match &bearer.hosts {
+                Some(hosts) => vec![hosts.clone()],
+                None if sites.is_empty() => {
+                    return Err(DownloadError::InvalidConfig(format!(
+                        "--bearer-token-env {} needs a host here, e.g. docs.example.com={}",
+                        bearer.var, bearer.var
+                    )))
+                }
+                None => {
+                    let hosts: Vec<HostPattern> = sites.iter().filter_map(Site::host_pattern).collect::<Result<_, _>>()?;
+                    // e.g. when validating a local file without --base-url
+                    if hosts.is_empty() {
+                        eprintln!(
+                            "⚠️  --bearer-token-env {} is not used, there is no website host to send it to; pass --base-url or scope it like docs.example.com={}",
+                            bearer.var, bearer.var
+                        );
+                    }
+                    hosts
+                }
+            };
+            for hosts in hosts {
+                builder = builder.bearer_token(hosts, &token);
+            }
+        }
+        for site in sites {
+            let Some(profile) = &site.profile else {
+                continue;
+            };
+            // Credentials of a profile go to its own host unless they name other hosts
+            let site_hosts = || {
+                site.host_pattern().unwrap_or_else(|| {
+                    Err(DownloadError::InvalidConfig(format!("site URL {} has no host", site.url)))
+                })
+            };
+            for header in &profile.header {
+                let hosts = header.hosts.clone().map_or_else(site_hosts, Ok)?;
+                builder = builder.host_header(hosts, &header.name, &header.value);
+            }
+            for bearer in &profile.bearer_token_env {
+                let hosts = bearer.hosts.clone().map_or_else(site_hosts, Ok)?;
+                builder = builder.bearer_token(hosts, &bearer.token()?);
+            }
+        }
+        if let Some(path) = self.netrc_path() {
+            builder = builder.netrc(path);
+        }
+        if let Some(path) = &self.cookie_jar {
+            builder = builder.cookie_jar(path);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(no_proxy) = &self.no_proxy {
+            builder = builder.no_proxy(no_proxy);
+        }
+        for path in &self.ca_certs {
+            builder = builder.ca_cert(path);
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => builder = builder.client_cert(cert, key),
+            (None, None) => {}
+            _ => {
+                return Err(DownloadError::InvalidConfig(
+                    "client-cert and client-key must be given together".to_string(),
+                ))
             }
         }
-        Err(e) => {
-            eprintln!("❌ Error: {e}");
-            std::process::exit(1);
+        if self.insecure {
+            eprintln!("⚠️  WARNING: --insecure turns off TLS certificate verification.");
+            eprintln!("⚠️  Anyone between you and the server can read and alter the downloaded files.");
+            builder = builder.insecure(true);
         }
+        
+        builder.build()
+    }
+
+    /// Path of the netrc file to use, if any
+    fn netrc_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.netrc_file {
+            return Some(path.clone());
+        }
+        if !self.netrc {
+            return None;
+        }
+        if let Some(path) = std::env::var_os("NETRC").filter(|path| !path.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        Some(PathBuf::from(home).join(".netrc"))
     }
-    
-    Ok(())
 }
 
-/// Main processing function that orchestrates the entire download workflow
-async fn process_url(base_url: &str, output_dir: &str, max_concurrent: usize) -> Result<DownloadResult, DownloadError> {
-    let client = HttpClient::new();
-    let mut result = DownloadResult::new();
+/// A `--header` argument
+#[derive(Debug, Clone)]
+struct HeaderArg {
+    /// Hosts the header is sent to, all hosts if None
+    hosts: Option<HostPattern>,
+    name: String,
+    value: String,
+}
+
+impl FromStr for HeaderArg {
+    type Err = String;
+
+    /// Parses a `Name: value` or `Host-Pattern: Name: value` header argument
+    /// 
+    /// The first part is taken as a host pattern when it contains a dot or a
+    /// `*`, or is `localhost`, which header names never are in practice. The
+    /// colon of a `http://` prefix does not end the host pattern.
+    fn from_str(header: &str) -> Result<Self, Self::Err> {
+        let expected = || "expected 'Name: value' or 'Host-Pattern: Name: value'".to_string();
+        let (first, rest) = header
+            .match_indices(':')
+            .find(|(index, _)| !header[index + 1..].starts_with("//"))
+            .map(|(index, _)| (&header[..index], &header[index + 1..]))
+            .filter(|(first, _)| !first.trim().is_empty())
+            .ok_or_else(expected)?;
     
-    // Step 1: Check for llms.txt file
-    let llms_txt_url = format!("{base_url}/llms.txt");
-    println!("🔍 Looking for llms.txt at: {llms_txt_url}");
+        if !HostPattern::looks_like(first) {
+            return Ok(HeaderArg { hosts: None, name: first.trim().to_string(), value: rest.trim().to_string() });
+        }
     
-    let llms_content = match client.fetch_content(&llms_txt_url).await {
-        Ok(content) => {
-            println!("✅ Found llms.txt file");
-            content
+        let (name, value) = rest
+            .split_once(':')
+            .filter(|(name, _)| !name.trim().is_empty())
+            .ok_or_else(expected)?;
+        let hosts = first.parse().map_err(|e: DownloadError| e.to_string())?;
+        Ok(HeaderArg { hosts: Some(hosts), name: name.trim().to_string(), value: value.trim().to_string() })
+    }
+}
+
+/// A `--bearer-token-env` argument
+#[derive(Debug, Clone)]
+struct BearerTokenEnv {
+    /// Hosts the token is sent to, the site's host if None
+    hosts: Option<HostPattern>,
+    /// Environment variable holding the token
+    var: String,
+}
+
+impl BearerTokenEnv {
+    /// Reads the token from the environment variable
+    fn token(&self) -> Result<String, DownloadError> {
+        std::env::var(&self.var)
+            .ok()
+            .filter(|token| !token.trim().is_empty())
+            .ok_or_else(|| DownloadError::InvalidConfig(format!("environment variable {} is not set", self.var)))
+    }
+}
+
+impl FromStr for BearerTokenEnv {
+    type Err = String;
+
+    /// Parses a `VAR` or `Host-Pattern=VAR` bearer token argument
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        // Environment variable names cannot contain `=`
+        let (hosts, var) = match arg.split_once('=') {
+            Some((hosts, var)) => (Some(hosts.parse().map_err(|e: DownloadError| e.to_string())?), var),
+            None => (None, arg),
+        };
+        if var.trim().is_empty() {
+            return Err("expected 'VAR' or 'Host-Pattern=VAR'".to_string());
+        }
+        Ok(BearerTokenEnv { hosts, var: var.trim().to_string() })
+    }
+}
+
+impl DownloadArgs {
+    /// Collects the website URLs from the command line and --from-file, without duplicates
+    /// 
+    /// Empty lines and lines starting with `#` in the file are ignored.
+    fn site_urls(&self) -> Result<Vec<String>, DownloadError> {
+        let mut urls = self.urls.clone();
+        
+        if let Some(path) = &self.from_file {
+            let content = if path.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())
+            } else {
+                std::fs::read_to_string(path)
+            };
+            let content = content.map_err(|e| {
+                DownloadError::InvalidConfig(format!("could not read site list {}: {e}", path.display()))
+            })?;
+            urls.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        
+        let mut seen = std::collections::HashSet::new();
+        urls.retain(|url| seen.insert(url.clone()));
+        if urls.is_empty() {
+            return Err(DownloadError::InvalidConfig("no website URLs given".to_string()));
         }
-        Err(e) => {
-            return Err(DownloadError::ParseError(format!(
-                "Could not find or access llms.txt at {llms_txt_url}: {e}"
+        Ok(urls)
+    }
+}
+
+impl SyncArgs {
+    /// Looks up the site profiles to download
+    fn sites(&self, config: &Config) -> Result<Vec<Site>, DownloadError> {
+        if config.sites.is_empty() {
+            return Err(DownloadError::InvalidConfig(format!(
+                "no site profiles, add [sites.<name>] tables to {} or pass --config",
+                config::CONFIG_FILE
             )));
         }
+        
+        let names: Vec<&String> = if self.all {
+            config.sites.keys().collect()
+        } else {
+            self.profiles.iter().collect()
+        };
+        let mut seen = std::collections::HashSet::new();
+        names
+            .into_iter()
+            .filter(|name| seen.insert(*name))
+            .map(|name| {
+                let profile = config.profile(name)?;
+                Ok(Site { url: profile.url.clone(), profile: Some(profile.clone()) })
+            })
+            .collect()
+    }
+}
+
+/// Resolves an on/off setting: `--flag` and its negation on the command line
+/// win over the config file, which wins over the default of off
+fn config_flag(enabled: bool, disabled: bool, config: Option<bool>) -> bool {
+    match (enabled, disabled) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => config.unwrap_or(false),
+    }
+}
+
+impl DownloadOptions {
+    /// Fills in the options left out on the command line from the config file
+    fn apply_config(&mut self, defaults: &Defaults) {
+        self.output = self.output.take().or_else(|| defaults.output.clone());
+        self.threads = self.threads.or(defaults.threads);
+        self.site_threads = self.site_threads.or(defaults.site_threads);
+        self.parallel_sites = self.parallel_sites.or(defaults.parallel_sites);
+        self.cross_origin = self.cross_origin.or(defaults.cross_origin);
+        self.respect_robots = config_flag(self.respect_robots, self.no_respect_robots, defaults.respect_robots);
+        self.no_cache = config_flag(self.no_cache, self.cache, defaults.no_cache);
+        self.prune = self.prune.or(defaults.prune);
+        if !self.no_report {
+            self.report = self.report.or(defaults.report);
+        }
+        self.fail_on = self.fail_on.or(defaults.fail_on);
+    }
+
+    /// Picks the section filter, index file and splitting for a site
+    /// 
+    /// Section filters and the choice of index file on the command line
+    /// replace those of the site profile, and switches the profile turns on
+    /// can be turned off with their `--no-` flag.
+    fn site_settings(&self, profile: &SiteProfile) -> Result<(SectionFilter, SourceMode, bool), DownloadError> {
+        let filter = SectionFilter {
+            include: if self.sections.is_empty() { profile.section.clone() } else { self.sections.clone() },
+            exclude: if self.exclude_sections.is_empty() { profile.exclude_section.clone() } else { self.exclude_sections.clone() },
+            skip_optional: config_flag(self.skip_optional, self.no_skip_optional, profile.skip_optional),
+        };
+        
+        let (full, prefer_full) = if self.full || self.prefer_full {
+            (self.full, self.prefer_full)
+        } else {
+            (profile.full, profile.prefer_full)
+        };
+        let source = match (full, prefer_full) {
+            (true, true) => {
+                return Err(DownloadError::InvalidConfig("full and prefer-full cannot be used together".to_string()))
+            }
+            (true, false) => SourceMode::Full,
+            (false, true) => SourceMode::PreferFull,
+            (false, false) => SourceMode::LlmsTxt,
+        };
+        let split = config_flag(self.split, self.no_split, profile.split);
+        if split && source == SourceMode::LlmsTxt {
+            return Err(DownloadError::InvalidConfig("--split needs --full or --prefer-full".to_string()));
+        }
+        Ok((filter, source, split))
+    }
+
+    /// Builds the downloader for a site from the shared settings in `builder`
+    /// and the settings picked by [`Self::site_settings`]
+    fn site_downloader(
+        &self,
+        builder: &DownloaderBuilder,
+        output: &Path,
+        profile: Option<&SiteProfile>,
+    ) -> Result<Downloader, DownloadError> {
+        let profile = profile.cloned().unwrap_or_default();
+        let (filter, source, split) = self.site_settings(&profile)?;
+        
+        let output_dir = match &profile.output {
+            Some(subdirectory) => output.join(subdirectory),
+            None => output.to_path_buf(),
+        };
+        
+        Ok(builder
+            .clone()
+            .output_dir(output_dir)
+            .filter(filter)
+            .source(source)
+            .split(split)
+            .build())
+    }
+}
+
+/// A website to download
+struct Site {
+    url: String,
+    /// The site profile in llmsdl.toml the website comes from, if any
+    profile: Option<SiteProfile>,
+}
+
+impl Site {
+    /// A website given on the command line
+    fn new(url: String) -> Self {
+        Self { url, profile: None }
+    }
+
+    /// Host pattern matching only the website's host, if the URL has one
+    fn host_pattern(&self) -> Option<Result<HostPattern, DownloadError>> {
+        Url::parse(&self.url).ok()?.host_str().map(str::parse)
+    }
+}
+
+/// Options for the validate subcommand
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// Local llms.txt file, llms.txt URL or website URL to validate
+    #[arg(help = "llms.txt file path, llms.txt URL or website URL")]
+    target: String,
+    
+    /// Base URL for resolving relative links in a local file
+    #[arg(long = "base-url", help = "Base URL for relative links when validating a local file")]
+    base_url: Option<String>,
+    
+    /// Check every link with a HEAD request
+    #[arg(long = "check-links", help = "Send HEAD requests to find dead links")]
+    check_links: bool,
+    
+    /// Number of concurrent link checks
+    #[arg(short = 't', long = "threads", default_value = "5", help = "Number of concurrent link checks")]
+    threads: usize,
+    
+    /// Treat warnings as errors
+    #[arg(long = "strict", help = "Exit with an error on warnings too")]
+    strict: bool,
+    
+    /// Output format for the validation report
+    #[arg(long = "format", value_enum, default_value = "text", help = "Output format")]
+    format: OutputFormat,
+}
+
+/// Output format for reports
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// Machine-readable JSON
+    Json,
+}
+
+/// Handling of files hosted on other hosts than llms.txt
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum CrossOrigin {
+    /// Save them in a directory named after their host
+    HostDir,
+    /// Do not download them
+    Reject,
+}
+
+impl From<CrossOrigin> for CrossOriginPolicy {
+    fn from(cross_origin: CrossOrigin) -> Self {
+        match cross_origin {
+            CrossOrigin::HostDir => CrossOriginPolicy::HostDirectory,
+            CrossOrigin::Reject => CrossOriginPolicy::Reject,
+        }
+    }
+}
+
+/// What to do with files that are no longer listed
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum Prune {
+    /// Delete them
+    Delete,
+    /// Move them to .llmsdl-removed/ in the domain directory
+    Quarantine,
+}
+
+impl From<Prune> for PruneMode {
+    fn from(prune: Prune) -> Self {
+        match prune {
+            Prune::Delete => PruneMode::Delete,
+            Prune::Quarantine => PruneMode::Quarantine,
+        }
+    }
+}
+
+/// Format of the end-of-run report
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum ReportKind {
+    /// A single JSON object
+    Json,
+    /// JUnit XML
+    Junit,
+    /// Markdown tables
+    Markdown,
+}
+
+impl From<ReportKind> for ReportFormat {
+    fn from(kind: ReportKind) -> Self {
+        match kind {
+            ReportKind::Json => ReportFormat::Json,
+            ReportKind::Junit => ReportFormat::Junit,
+            ReportKind::Markdown => ReportFormat::Markdown,
+        }
+    }
+}
+
+/// Format of the per-file event stream
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum EventsFormat {
+    /// One JSON object per line
+    Ndjson,
+}
+
+/// Which download outcomes count as a failure for the exit code
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum FailOn {
+    /// Fail when any file could not be downloaded
+    Any,
+    /// Accept partial downloads, only fail when files failed and none could be downloaded
+    AllFailed,
+    /// Only fail when the download could not run at all
+    None,
+}
+
+impl FailOn {
+    /// Pick the exit code for the outcome of a download
+    /// 
+    /// # Arguments
+    /// * `completed` - Number of files that were downloaded or already up to date
+    /// * `failures` - Number of files and websites that failed
+    /// * `dry_run` - Whether nothing was downloaded, so there is nothing to fail
+    /// 
+    /// # Returns
+    /// * `Exit` - The exit code of the process
+    fn exit(self, completed: usize, failures: usize, dry_run: bool) -> Exit {
+        if dry_run || failures == 0 {
+            return Exit::Success;
+        }
+        match self {
+            FailOn::None => Exit::Success,
+            FailOn::Any if completed > 0 => Exit::PartialFailure,
+            FailOn::AllFailed if completed > 0 => Exit::Success,
+            FailOn::Any | FailOn::AllFailed => Exit::TotalFailure,
+        }
+    }
+}
+
+/// Exit codes of the process
+/// 
+/// Codes 2 to 7 are stable so scripts can tell failures apart, e.g. a
+/// website without llms.txt from a run where some files failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    /// Everything succeeded
+    Success = 0,
+    /// Any other error, or an llms.txt file that failed validation
+    Failure = 1,
+    /// Invalid arguments or URL
+    Usage = 2,
+    /// llms.txt (or llms-full.txt) could not be found or fetched
+    LlmsTxtNotFound = 3,
+    /// llms.txt could not be parsed
+    ParseFailure = 4,
+    /// Some files could not be downloaded
+    PartialFailure = 5,
+    /// No file could be downloaded
+    TotalFailure = 6,
+    /// Files or directories could not be written
+    Filesystem = 7,
+    /// Interrupted by SIGINT (Ctrl-C)
+    Interrupted = 130,
+    /// Terminated by SIGTERM
+    Terminated = 143,
+}
+
+impl Exit {
+    /// Pick the exit code for an error that stopped the program
+    fn from_error(error: &DownloadError) -> Self {
+        match error {
+            DownloadError::InvalidUrl(_) | DownloadError::InvalidConfig(_) => Exit::Usage,
+            DownloadError::LlmsTxtUnavailable { .. } => Exit::LlmsTxtNotFound,
+            DownloadError::ParseError(_) => Exit::ParseFailure,
+            DownloadError::IoError(_) | DownloadError::UnsafePath(_) => Exit::Filesystem,
+            _ => Exit::Failure,
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+/// Exit code table shown at the end of `--help`
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    Success
+  1    Other error, or llms.txt failed validation
+  2    Invalid arguments or URL
+  3    llms.txt not found
+  4    llms.txt could not be parsed
+  5    Some files failed to download (see --fail-on)
+  6    No file could be downloaded (see --fail-on)
+  7    File system error
+  130  Interrupted (SIGINT)
+  143  Terminated (SIGTERM)";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    
+    let exit = tokio::select! {
+        exit = run(cli) => exit,
+        exit = interrupted() => {
+            eprintln!("\n⚠️  Interrupted, partially downloaded files are kept and resumed on the next run");
+            exit
+        }
     };
+    exit.into()
+}
+
+/// Runs the selected command and reports errors
+async fn run(cli: Cli) -> Exit {
+    execute(cli).await.unwrap_or_else(|e| {
+        eprintln!("❌ Error: {e}");
+        for hint in cli_hints([e.kind()]) {
+            eprintln!("💡 {hint}");
+        }
+        Exit::from_error(&e)
+    })
+}
+
+/// Suggests the options that help with the kinds of errors that occurred
+/// 
+/// The messages and troubleshooting recommendations of the library do not
+/// know about the command line, so its options are pointed out here.
+fn cli_hints(kinds: impl IntoIterator<Item = ErrorKind>) -> Vec<&'static str> {
+    let kinds: BTreeSet<ErrorKind> = kinds.into_iter().collect();
+    kinds
+        .into_iter()
+        .filter_map(|kind| match kind {
+            ErrorKind::Tls => Some("If a proxy intercepts HTTPS traffic, pass its CA certificate with --ca-cert"),
+            ErrorKind::Auth => Some("Pass credentials with --bearer-token-env, --netrc, --cookie-jar or a host-scoped --header"),
+            ErrorKind::RateLimited => Some("Lower the number of concurrent downloads with -t, or the request rate with --rate"),
+            ErrorKind::UnsafePath => Some("Use --cross-origin host-dir to keep files from other hosts in their own directory"),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Runs the selected command with the settings of the config file
+async fn execute(cli: Cli) -> Result<Exit, DownloadError> {
+    let config = Config::load(cli.config.as_deref())?.unwrap_or_default();
+    let mut http = cli.http;
+    http.apply_config(&config.defaults);
     
-    // Step 2: Parse llms.txt content to get file URLs
-    println!("📝 Parsing llms.txt content...");
-    let file_urls = parse_llms_txt(&llms_content, base_url)?;
+    match cli.command {
+        Some(Command::Validate(args)) => {
+            let site = Site::new(args.base_url.clone().unwrap_or_else(|| args.target.clone()));
+            let client = http.client(&[site])?;
+            let passed = run_validate(&args, client).await?;
+            Ok(if passed { Exit::Success } else { Exit::Failure })
+        }
+        Some(Command::Sync(mut args)) => {
+            let sites = args.sites(&config)?;
+            let client = http.client(&sites)?;
+            args.options.apply_config(&config.defaults);
+            run_download(args.options, sites, client).await
+        }
+        Some(Command::List(mut args)) => {
+            args.check()?;
+            let sites: Vec<Site> = args.sites.site_urls()?.into_iter().map(Site::new).collect();
+            let client = http.client(&sites)?;
+            args.sites.options.apply_config(&config.defaults);
+            run_list(args.sites.options, sites, client, args.format).await
+        }
+        // Without a subcommand clap requires the download arguments
+        None => {
+            let mut args = cli.download;
+            let sites: Vec<Site> = args.site_urls()?.into_iter().map(Site::new).collect();
+            let client = http.client(&sites)?;
+            args.options.apply_config(&config.defaults);
+            run_download(args.options, sites, client).await
+        }
+    }
+}
+
+/// Waits for SIGINT or SIGTERM
+async fn interrupted() -> Exit {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = tokio::signal::ctrl_c() => Exit::Interrupted,
+                _ = terminate.recv() => Exit::Terminated,
+            };
+        }
+    }
     
-    if file_urls.is_empty() {
-        println!("⚠️  No files found in llms.txt");
-        return Ok(result);
+    match tokio::signal::ctrl_c().await {
+        Ok(()) => Exit::Interrupted,
+        // Without a signal handler there is nothing to wait for
+        Err(_) => std::future::pending().await,
+    }
+}
+
+/// Downloads the files listed in the llms.txt of one or more websites
+/// Returns the exit code for the outcome of the downloads
+async fn run_download(options: DownloadOptions, sites: Vec<Site>, client: HttpClient) -> Result<Exit, DownloadError> {
+    if options.report_file.is_some() && options.report.is_none() {
+        return Err(DownloadError::InvalidConfig("--report-file needs --report".to_string()));
+    }
+    // Both would be interleaved on stdout
+    if options.events.is_some() && options.events_file.is_none() && options.report.is_some() && options.report_file.is_none() {
+        return Err(DownloadError::InvalidConfig(
+            "--events and --report both write to stdout, pass --events-file or --report-file".to_string(),
+        ));
     }
     
-    println!("📋 Found {} files to download", file_urls.len());
+    // Machine-readable output replaces the human progress output
+    let human_output = options.report.is_none() && options.events.is_none();
+    let batch = download_batch(&options, &sites, client, human_output).await?;
     
-    // Step 3: Create local directory structure
-    println!("📁 Creating local directory structure...");
-    let base_dir = create_local_directory(base_url, output_dir)?;
-    println!("📁 Files will be saved to: {}", base_dir.display());
+    if let Some(kind) = options.report {
+        let report = batch_report(&batch, kind.into());
+        match &options.report_file {
+            Some(path) => std::fs::write(path, report)?,
+            None => print!("{report}"),
+        }
+    }
     
-    // Step 4: Download files concurrently with enhanced progress reporting
-    println!("\n🚀 Starting concurrent downloads with {} threads...", max_concurrent);
+    // When no website could be processed at all, the first error decides the exit code
+    if batch.failed_site_count() == batch.sites.len() {
+        if let Some(Err(e)) = batch.sites.into_iter().next().map(|site| site.outcome) {
+            return Err(e);
+        }
+        return Ok(Exit::TotalFailure);
+    }
     
-    // Create semaphore to limit concurrent downloads
-    let semaphore = Arc::new(Semaphore::new(max_concurrent));
+    let result = batch.combined();
+    let failures = result.failure_count() + batch.failed_site_count();
+    if options.dry_run {
+        if human_output {
+            println!("\n🔍 Dry run completed, nothing was downloaded or changed");
+            print_plan(&batch, true);
+        }
+    } else if human_output {
+        // Display final summary
+        println!("\n✅ Process completed!");
+        match batch.sites.as_slice() {
+            [_] => println!("{result}"),
+            _ => println!("{batch}"),
+        }
+        
+        if failures == 0 && result.completed_count() > 0 {
+            println!("🎉 All files downloaded successfully!");
+        } else if result.completed_count() > 0 {
+            println!("⚠️  Some files failed to download, but {} files were successful.", result.completed_count());
+        } else {
+            println!("❌ No files were downloaded successfully.");
+        }
+        
+        let site_errors = batch.failed_sites().map(|(_, e)| e.kind());
+        for hint in cli_hints(result.error_stats().into_keys().chain(site_errors)) {
+            println!("💡 {hint}");
+        }
+        print_prune_hint(result.changes.as_ref());
+    }
     
-    // Create multi-progress for concurrent downloads
-    let multi_progress = Arc::new(MultiProgress::new());
+    let fail_on = options.fail_on.unwrap_or(FailOn::Any);
+    Ok(fail_on.exit(result.completed_count(), failures, options.dry_run))
+}
+
+/// Lists the files the websites would download and where they would be saved
+/// Returns the exit code for the outcome of the listing
+async fn run_list(mut options: DownloadOptions, sites: Vec<Site>, client: HttpClient, format: OutputFormat) -> Result<Exit, DownloadError> {
+    options.dry_run = true;
+    let batch = download_batch(&options, &sites, client, false).await?;
     
-    // Create overall progress bar
-    let overall_progress = multi_progress.add(ProgressBar::new(file_urls.len() as u64));
-    overall_progress.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) {msg}")
-            .unwrap()
-            .progress_chars("#>-")
-    );
-    overall_progress.set_message("Downloading files...");
+    match format {
+        OutputFormat::Text => print_plan(&batch, false),
+        OutputFormat::Json => print!("{}", batch_report(&batch, ReportFormat::Json)),
+    }
     
-    // Create download tasks for concurrent execution
-    let total_files = file_urls.len();
-    let download_tasks: Vec<_> = file_urls
-        .into_iter()
-        .enumerate()
-        .map(|(index, file_url)| {
-            let client = client.clone();
-            let base_dir = base_dir.clone();
-            let multi_progress = Arc::clone(&multi_progress);
-            let overall_progress = overall_progress.clone();
-            let semaphore = Arc::clone(&semaphore);
-            
-            tokio::spawn(async move {
-                // Acquire semaphore permit to limit concurrency
-                let _permit = semaphore.acquire().await.unwrap();
-                
-                let filename = file_url.split('/').next_back().unwrap_or(&file_url);
-                
-                // Create individual progress bar for this download
-                let file_progress = multi_progress.add(ProgressBar::new_spinner());
-                file_progress.set_style(
-                    ProgressStyle::default_spinner()
-                        .template("{spinner:.blue} [{elapsed_precise}] {msg}")
-                        .unwrap()
-                );
-                file_progress.set_message(format!("[{}/{}] {}", index + 1, total_files, filename));
-                
-                let result = download_single_file_with_progress(&client, &file_url, &base_dir, &file_progress).await;
-                
-                match &result {
-                    Ok((_local_path, bytes)) => {
-                        let size_str = crate::error::DownloadResult::format_bytes(*bytes);
-                        file_progress.finish_with_message(format!("✅ {filename} - {size_str}"));
-                    }
-                    Err(e) => {
-                        file_progress.finish_with_message(format!("❌ {filename} - {e}"));
-                    }
-                }
-                
-                overall_progress.inc(1);
-                (file_url, result)
-            })
-        })
-        .collect();
+    if batch.failed_site_count() == batch.sites.len() {
+        if let Some(Err(e)) = batch.sites.into_iter().next().map(|site| site.outcome) {
+            return Err(e);
+        }
+        return Ok(Exit::TotalFailure);
+    }
+    // Like a dry run, a listing only fails when no website could be processed
+    Ok(Exit::Success)
+}
+
+/// Downloads (or, in a dry run, plans) the websites with the command line settings
+/// 
+/// # Arguments
+/// * `options` - Download settings, with the config file applied
+/// * `sites` - The websites to download
+/// * `client` - The HTTP client shared by all websites
+/// * `show_progress` - Whether to print per-file progress and status messages
+async fn download_batch(
+    options: &DownloadOptions,
+    sites: &[Site],
+    client: HttpClient,
+    show_progress: bool,
+) -> Result<BatchResult, DownloadError> {
+    // Validate the URL formats up front, a typo should not fail halfway through a batch
+    let validated_urls = sites.iter().map(|site| validate_url(&site.url)).collect::<Result<Vec<_>, _>>()?;
     
-    // Wait for all downloads to complete
-    let download_results = join_all(download_tasks).await;
+    let output = options.output.clone().ok_or_else(|| {
+        DownloadError::InvalidConfig(format!("no output directory, pass --output or set output in {}", config::CONFIG_FILE))
+    })?;
     
-    // Process results
-    for task_result in download_results {
-        match task_result {
-            Ok((file_url, download_result)) => {
-                match download_result {
-                    Ok((local_path, bytes)) => {
-                        result.add_success(file_url, local_path.display().to_string(), bytes);
-                    }
-                    Err(e) => {
-                        result.add_failure(file_url, e.to_string());
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Task error: {e}");
-            }
+    if show_progress {
+        match validated_urls.as_slice() {
+            [url] => println!("🔍 Processing URL: {url}"),
+            urls => println!("🔍 Processing {} websites", urls.len()),
         }
     }
     
-    overall_progress.finish_with_message("All downloads completed");
-    println!(); // Add spacing after progress bars
+    let threads = options.threads.unwrap_or(DEFAULT_THREADS);
+    let mut builder = Downloader::builder()
+        .client(client)
+        .max_concurrent(options.site_threads.unwrap_or(threads))
+        .total_concurrent(threads)
+        .use_cache(!options.no_cache)
+        .cross_origin(options.cross_origin.unwrap_or(CrossOrigin::HostDir).into())
+        .respect_robots(options.respect_robots)
+        .prune(options.prune.map_or(PruneMode::Keep, PruneMode::from))
+        .dry_run(options.dry_run)
+        .show_progress(show_progress);
+    
+    if options.events.is_some() {
+        let writer = match &options.events_file {
+            Some(path) => NdjsonWriter::create(path)?,
+            None => NdjsonWriter::stdout(),
+        };
+        builder = builder.events(Arc::new(writer));
+    }
     
-    Ok(result)
+    // Every site gets its own filters and output directory, sharing the client and thread budget
+    let downloaders = sites
+        .iter()
+        .zip(validated_urls)
+        .map(|(site, url)| Ok((url, options.site_downloader(&builder, &output, site.profile.as_ref())?)))
+        .collect::<Result<Vec<_>, DownloadError>>()?;
+    
+    // A single website keeps the detailed per-file progress output
+    let batch = if let [(url, downloader)] = downloaders.as_slice() {
+        let mut batch = BatchResult::new();
+        let outcome = downloader.download(url).await;
+        batch.sites.push(SiteResult { url: url.clone(), outcome });
+        batch
+    } else {
+        Downloader::download_sites(downloaders, options.parallel_sites.unwrap_or(DEFAULT_PARALLEL_SITES)).await
+    };
+    Ok(batch)
 }
 
+/// Renders the end-of-run report, for a single website or a batch
+fn batch_report(batch: &BatchResult, format: ReportFormat) -> String {
+    match batch.sites.as_slice() {
+        [site] => render_report(&site.url, site.outcome.as_ref(), format),
+        _ => render_batch_report(batch, format),
+    }
+}
 
+/// Prints the files every website would download, and with `changes`
+/// what that would change compared to the previous run
+fn print_plan(batch: &BatchResult, changes: bool) {
+    for (index, site) in batch.sites.iter().enumerate() {
+        if index > 0 || changes {
+            println!();
+        }
+        if batch.sites.len() > 1 {
+            println!("🌐 {}", site.domain());
+        }
+        
+        let result = match &site.outcome {
+            Ok(result) => result,
+            Err(e) => {
+                println!("❌ {e}");
+                continue;
+            }
+        };
+        print_planned_files(&result.planned);
+        for skipped in &result.skipped {
+            println!("🚫 {}: {}", skipped.url, skipped.reason);
+        }
+        for failure in &result.failed {
+            println!("❌ {}: {}", failure.url, failure.error);
+        }
+        if let Some(changes) = result.changes.as_ref().filter(|_| changes) {
+            println!();
+            print!("{changes}");
+            print_prune_hint(Some(changes));
+        }
+    }
+}
+
+/// Points out --prune when files that are no longer listed were kept
+fn print_prune_hint(changes: Option<&Changes>) {
+    if changes.is_some_and(|changes| changes.prune == PruneMode::Keep && !changes.removed.is_empty()) {
+        println!("💡 Pass --prune to remove files that are no longer listed");
+    }
+}
 
-/// Downloads a single file with progress reporting for concurrent downloads
-/// Returns the local path and number of bytes downloaded
-async fn download_single_file_with_progress(
-    client: &HttpClient,
-    file_url: &str,
-    base_dir: &std::path::Path,
-    progress: &ProgressBar,
-) -> Result<(std::path::PathBuf, u64), DownloadError> {
-    // Determine the local file path
-    let local_path = get_local_file_path(file_url, base_dir)?;
+/// Prints planned files as a table of section, title, URL and local path
+fn print_planned_files(files: &[PlannedFile]) {
+    if files.is_empty() {
+        println!("No files to download");
+        return;
+    }
     
-    // Update progress to show we're starting
-    progress.set_message(format!("Starting download: {}", 
-        file_url.split('/').next_back().unwrap_or(file_url)));
+    let header = ["SECTION", "TITLE", "URL", "PATH"];
+    let rows: Vec<[&str; 4]> = files
+        .iter()
+        .map(|file| {
+            [
+                file.section.as_deref().unwrap_or("-"),
+                file.title.as_deref().unwrap_or("-"),
+                &file.url,
+                &file.path,
+            ]
+        })
+        .collect();
     
-    // Download the file and get byte count
-    let bytes = client.download_file(file_url, &local_path).await?;
+    let mut widths = header.map(|title| title.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row.iter().zip(widths).map(|(cell, width)| format!("{cell:<width$}")).collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+    println!("{} file(s)", files.len());
+}
+
+/// Validates an llms.txt file and prints the report
+/// Returns whether the file passed validation
+async fn run_validate(args: &ValidateArgs, client: HttpClient) -> Result<bool, DownloadError> {
+    let local_path = std::path::Path::new(&args.target);
     
-    Ok((local_path, bytes))
+    // Load the content from a local file or over HTTP
+    let (content, base_url) = if local_path.is_file() {
+        let content = tokio::fs::read_to_string(local_path).await?;
+        let base_url = match &args.base_url {
+            Some(base_url) => Some(validate_url(base_url)?),
+            None => None,
+        };
+        (content, base_url)
+    } else {
+        let target = validate_url(&args.target)?;
+        // Other .txt files are validated as they are, websites are searched for llms.txt
+        let found = if target.ends_with(".txt") && IndexFile::of_url(&target).is_none() {
+            let content = client.fetch_content(&target).await.map_err(|e| DownloadError::LlmsTxtUnavailable {
+                url: target.clone(),
+                source: Box::new(e),
+                tried: vec![target.clone()],
+            })?;
+            Discovered { url: target, content: Some(content), tried: Vec::new() }
+        } else {
+            // The trailing slash validate_url drops marks a directory
+            discover(&client, args.target.trim(), IndexFile::LlmsTxt, true).await?
+        };
+        if args.format == OutputFormat::Text && !found.tried.is_empty() {
+            println!("🔍 Found llms.txt at: {}", found.url);
+        }
+        (found.content.unwrap_or_default(), Some(found.url))
+    };
+    
+    let mut report = validate_llms_txt(&content, &args.target);
+    
+    if args.check_links {
+        let base_url = base_url.ok_or_else(|| {
+            DownloadError::InvalidUrl("--base-url is required to check links in a local file".to_string())
+        })?;
+        let base = Url::parse(&base_url)?;
+        if args.format == OutputFormat::Text {
+            println!("🔗 Checking {} link(s)...", report.link_count());
+        }
+        report.check_links(&client, &base, args.threads).await;
+    }
+    
+    match args.format {
+        OutputFormat::Text => println!("{report}"),
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| DownloadError::ParseError(format!("Failed to serialize report: {e}")))?;
+            println!("{json}");
+        }
+    }
+    
+    let failed = report.has_errors() || (args.strict && report.warning_count() > 0);
+    Ok(!failed)
 }
 
-/// Validates the provided URL and ensures it's properly formatted
-fn validate_url(url_str: &str) -> Result<String, DownloadError> {
-    // Parse the URL to validate its format
-    let parsed_url = Url::parse(url_str)
-        .map_err(|e| DownloadError::InvalidUrl(format!("Invalid URL format: {e}")))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
     
-    // Ensure the URL has a valid scheme (http or https)
-    match parsed_url.scheme() {
-        "http" | "https" => {},
-        scheme => return Err(DownloadError::InvalidUrl(
-            format!("Unsupported URL scheme '{scheme}'. Only http and https are supported.")
-        )),
+    #[test]
+    fn fail_on_picks_exit_codes() {
+        // (policy, no failures, some failed, all failed)
+        let cases = [
+            (FailOn::Any, Exit::Success, Exit::PartialFailure, Exit::TotalFailure),
+            (FailOn::AllFailed, Exit::Success, Exit::Success, Exit::TotalFailure),
+            (FailOn::None, Exit::Success, Exit::Success, Exit::Success),
+        ];
+        for (fail_on, clean, partial, total) in cases {
+            assert_eq!(fail_on.exit(3, 0, false), clean, "{fail_on:?}");
+            assert_eq!(fail_on.exit(2, 1, false), partial, "{fail_on:?}");
+            assert_eq!(fail_on.exit(0, 3, false), total, "{fail_on:?}");
+            
+            // A dry run downloads nothing, whatever the outcome of planning
+            for (completed, failures) in [(3, 0), (2, 1), (0, 3)] {
+                assert_eq!(fail_on.exit(completed, failures, true), Exit::Success, "{fail_on:?}");
+            }
+        }
     }
     
-    // Ensure the URL has a host
-    if parsed_url.host().is_none() {
-        return Err(DownloadError::InvalidUrl(
-            "URL must have a valid host".to_string()
-        ));
+    #[test]
+    fn fail_on_parses_kebab_case() {
+        assert_eq!(<FailOn as ValueEnum>::from_str("all-failed", false), Ok(FailOn::AllFailed));
+        assert!(<FailOn as ValueEnum>::from_str("partial", false).is_err());
+        
+        let defaults: Defaults = toml::from_str("fail-on = \"all-failed\"").unwrap();
+        assert_eq!(defaults.fail_on, Some(FailOn::AllFailed));
+    }
+    
+    /// Parse the download options of a command line
+    fn options(args: &[&str]) -> DownloadOptions {
+        let cli = Cli::try_parse_from(["llmsdl", "https://example.com"].iter().chain(args)).unwrap();
+        cli.download.options
+    }
+    
+    #[test]
+    fn command_line_turns_off_profile_switches() {
+        let profile = SiteProfile {
+            skip_optional: Some(true),
+            prefer_full: true,
+            split: Some(true),
+            ..SiteProfile::default()
+        };
+        
+        let (filter, _, split) = options(&[]).site_settings(&profile).unwrap();
+        assert!(filter.skip_optional && split);
+        
+        let (filter, _, split) = options(&["--no-skip-optional", "--no-split"]).site_settings(&profile).unwrap();
+        assert!(!filter.skip_optional && !split);
+        
+        // The last of a flag and its negation wins
+        let (filter, _, split) = options(&["--no-skip-optional", "--skip-optional", "--split", "--no-split"])
+            .site_settings(&profile)
+            .unwrap();
+        assert!(filter.skip_optional && !split);
+    }
+    
+    #[test]
+    fn command_line_turns_off_config_report() {
+        let defaults: Defaults = toml::from_str("report = \"json\"").unwrap();
+        
+        let mut default_options = options(&[]);
+        default_options.apply_config(&defaults);
+        assert_eq!(default_options.report, Some(ReportKind::Json));
+        
+        let mut no_report = options(&["--no-report"]);
+        no_report.apply_config(&defaults);
+        assert_eq!(no_report.report, None);
+        
+        let mut markdown = options(&["--no-report", "--report", "markdown"]);
+        markdown.apply_config(&defaults);
+        assert_eq!(markdown.report, Some(ReportKind::Markdown));
     }
     
-    // Return the URL without trailing slash for consistency
-    let mut clean_url = parsed_url.to_string();
-    if clean_url.ends_with('/') {
-        clean_url.pop();
+    #[test]
+    fn header_args_take_a_scheme_in_the_host_pattern() {
+        let header: HeaderArg = "http://localhost: X-Api-Key: secret".parse().unwrap();
+        assert_eq!(header.hosts.unwrap().to_string(), "http://localhost");
+        assert_eq!((header.name.as_str(), header.value.as_str()), ("X-Api-Key", "secret"));
+        
+        let header: HeaderArg = "Accept-Language: en".parse().unwrap();
+        assert!(header.hosts.is_none());
+        assert_eq!((header.name.as_str(), header.value.as_str()), ("Accept-Language", "en"));
     }
     
-    Ok(clean_url)
+    #[test]
+    fn cli_hints_name_options_once_per_kind() {
+        assert_eq!(cli_hints([ErrorKind::Tls, ErrorKind::NotFound, ErrorKind::Tls]).len(), 1);
+        assert!(cli_hints([ErrorKind::Auth])[0].contains("--bearer-token-env"));
+        assert!(cli_hints([ErrorKind::NotFound, ErrorKind::Network]).is_empty());
+    }
+    
+    #[test]
+    fn list_rejects_download_only_options() {
+        let list = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["llmsdl", "list", "https://example.com"].iter().chain(args)).unwrap();
+            let Some(Command::List(args)) = cli.command else {
+                panic!("not the list subcommand");
+            };
+            args.check()
+        };
+        
+        assert!(list(&[]).is_ok());
+        assert!(list(&["--format", "json", "--skip-optional"]).is_ok());
+        for args in [&["--report", "json"][..], &["--events", "ndjson"], &["--prune", "delete"], &["--fail-on", "none"]] {
+            assert!(matches!(list(args), Err(DownloadError::InvalidConfig(_))), "{args:?}");
+        }
+    }
 }
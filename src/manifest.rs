@@ -0,0 +1,140 @@
+use crate::cache::FileMetadata;
+use crate::error::{DownloadError, DownloadFailure, DownloadResult, SkippedFile};
+use crate::file_manager::relative_path;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Name of the manifest written to each domain directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Machine-readable record of a download run
+/// 
+/// Written as `manifest.json` into the domain directory after every run, so
+/// downstream jobs can tell which files are fresh and where they came from
+/// without crawling the website again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the manifest format
+    pub version: u32,
+    /// When the run finished, as an RFC 3339 timestamp
+    pub generated_at: String,
+    /// The index file the run started from
+    pub source: ManifestSource,
+    /// Files that are up to date locally, in the order they are listed
+    pub files: Vec<ManifestFile>,
+    /// Files that could not be downloaded, with the kind and source chain of the error
+    pub failures: Vec<DownloadFailure>,
+    /// Files that were not downloaded because a policy forbids it
+    #[serde(default)]
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// The llms.txt (or llms-full.txt) a run started from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestSource {
+    /// URL of the index file
+    pub url: String,
+    /// Hex-encoded SHA-256 of the index file content
+    pub sha256: String,
+}
+
+/// A file that is up to date locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// URL the file was downloaded from
+    pub url: String,
+    /// Path relative to the domain directory, with `/` separators
+    pub path: String,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the file content
+    pub sha256: String,
+    /// `Content-Type` response header
+    pub content_type: Option<String>,
+    /// HTTP status of the last request for the file: 200 or 206 when it was
+    /// downloaded, 304 when it was unchanged, none when it was split out of
+    /// llms-full.txt
+    pub status: Option<u16>,
+    /// When the file was downloaded or revalidated, as an RFC 3339 timestamp
+    pub fetched_at: String,
+}
+
+impl Manifest {
+    /// Current version of the manifest format
+    const VERSION: u32 = 1;
+
+    /// Create an empty manifest for a run starting from the given index file
+    pub fn new(source_url: &str, source_sha256: String) -> Self {
+        Self {
+            version: Self::VERSION,
+            generated_at: timestamp(SystemTime::now()),
+            source: ManifestSource {
+                url: source_url.to_string(),
+                sha256: source_sha256,
+            },
+            files: Vec::new(),
+            failures: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Load the manifest stored in a domain directory, if there is a readable one
+    pub fn load(domain_dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(domain_dir.join(MANIFEST_FILE_NAME)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Record a file that is up to date locally
+    /// 
+    /// # Arguments
+    /// * `domain_dir` - The domain directory `local_path` is relative to
+    /// * `url` - The URL the file was downloaded from
+    /// * `local_path` - Where the file is saved
+    /// * `metadata` - Size, hash and content type of the file
+    /// * `status` - HTTP status of the last request for the file
+    /// * `fetched_at` - When that request finished
+    pub fn add_file(
+        &mut self,
+        domain_dir: &Path,
+        url: &str,
+        local_path: &Path,
+        metadata: &FileMetadata,
+        status: Option<u16>,
+        fetched_at: SystemTime,
+    ) {
+        self.files.push(ManifestFile {
+            url: url.to_string(),
+            path: relative_path(domain_dir, local_path).unwrap_or_else(|| local_path.display().to_string()),
+            size: metadata.size,
+            sha256: metadata.sha256.clone(),
+            content_type: metadata.content_type.clone(),
+            status,
+            fetched_at: timestamp(fetched_at),
+        });
+    }
+
+    /// Record the failures of a run
+    pub fn add_failures(&mut self, result: &DownloadResult) {
+        self.failures.extend(result.failed.iter().cloned());
+    }
+
+    /// Record the files a run skipped by policy
+    pub fn add_skipped(&mut self, result: &DownloadResult) {
+        self.skipped.extend(result.skipped.iter().cloned());
+    }
+
+    /// Write the manifest to the domain directory
+    pub fn save(&self, domain_dir: &Path) -> Result<(), DownloadError> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| DownloadError::ParseError(format!("Failed to serialize manifest: {e}")))?;
+        fs::write(domain_dir.join(MANIFEST_FILE_NAME), content)?;
+        Ok(())
+    }
+}
+
+/// Format a point in time as an RFC 3339 timestamp in UTC
+fn timestamp(time: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(time).to_string()
+}
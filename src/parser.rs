@@ -1,10 +1,133 @@
 use crate::error::DownloadError;
 use url::Url;
 
-/// Parse llms.txt content and extract file paths
+/// A parsed llms.txt file following the structure described at llmstxt.org
+/// 
+/// The spec defines an H1 title, an optional blockquote summary, optional
+/// free-form details and any number of H2 sections containing link lists.
+#[derive(Debug, Clone, Default)]
+pub struct LlmsTxtDocument {
+    /// Project or site name from the H1 heading
+    pub title: Option<String>,
+    /// Short summary from the blockquote following the title
+    pub summary: Option<String>,
+    /// Free-form text between the summary and the first section
+    pub details: Option<String>,
+    /// Sections of file entries, in document order
+    pub sections: Vec<LlmsTxtSection>,
+}
+
+/// An H2 section of an llms.txt file and the entries listed under it
+#[derive(Debug, Clone, Default)]
+pub struct LlmsTxtSection {
+    /// Section name from the H2 heading, or None for entries listed before any heading
+    pub name: Option<String>,
+    /// File entries listed in this section
+    pub entries: Vec<LlmsTxtEntry>,
+}
+
+/// A single file entry from an llms.txt file
+#[derive(Debug, Clone)]
+pub struct LlmsTxtEntry {
+    /// Link text for markdown links, None for plain file paths
+    pub title: Option<String>,
+    /// Absolute URL of the file
+    pub url: String,
+    /// Text following the link, e.g. `- [Title](/path.md): Description`
+    pub description: Option<String>,
+}
+
+impl LlmsTxtDocument {
+    /// Iterate over all entries in document order, across every section
+    pub fn entries(&self) -> impl Iterator<Item = &LlmsTxtEntry> {
+        self.sections.iter().flat_map(|section| section.entries.iter())
+    }
+
+    /// Total number of entries across all sections
+    pub fn entry_count(&self) -> usize {
+        self.sections.iter().map(|section| section.entries.len()).sum()
+    }
+
+    /// Check if the document lists no files at all
+    pub fn is_empty(&self) -> bool {
+        self.entry_count() == 0
+    }
+
+    /// Find an entry by its link title, along with the section it is listed in
+    /// 
+    /// Titles are compared case-insensitively, ignoring surrounding whitespace.
+    pub fn find_by_title(&self, title: &str) -> Option<(&LlmsTxtSection, &LlmsTxtEntry)> {
+        let title = title.trim();
+        self.sections.iter().find_map(|section| {
+            section
+                .entries
+                .iter()
+                .find(|entry| entry.title.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(title)))
+                .map(|entry| (section, entry))
+        })
+    }
+
+    /// Remove the sections rejected by the filter
+    /// 
+    /// # Returns
+    /// * `usize` - The number of entries that were removed
+    pub fn apply_filter(&mut self, filter: &SectionFilter) -> usize {
+        let before = self.entry_count();
+        self.sections.retain(|section| filter.allows(section.name.as_deref()));
+        before - self.entry_count()
+    }
+}
+
+/// Selects which sections of an llms.txt document should be downloaded
+/// 
+/// Section names are matched case-insensitively. Entries listed before
+/// the first H2 heading have no section and are only kept when no
+/// explicit `include` list is given.
+#[derive(Debug, Clone, Default)]
+pub struct SectionFilter {
+    /// Only keep these sections (all sections when empty)
+    pub include: Vec<String>,
+    /// Drop these sections
+    pub exclude: Vec<String>,
+    /// Drop the `Optional` section defined by the llms.txt spec
+    pub skip_optional: bool,
+}
+
+impl SectionFilter {
+    /// Name of the section the llms.txt spec marks as skippable
+    pub const OPTIONAL_SECTION: &'static str = "Optional";
+
+    /// Check if a section with the given name passes the filter
+    pub fn allows(&self, name: Option<&str>) -> bool {
+        let matches = |names: &[String], name: &str| {
+            names.iter().any(|candidate| candidate.trim().eq_ignore_ascii_case(name))
+        };
+        
+        match name {
+            Some(name) => {
+                if self.skip_optional && name.eq_ignore_ascii_case(Self::OPTIONAL_SECTION) {
+                    return false;
+                }
+                if matches(&self.exclude, name) {
+                    return false;
+                }
+                self.include.is_empty() || matches(&self.include, name)
+            }
+            None => self.include.is_empty(),
+        }
+    }
+
+    /// Check if the filter would keep every section
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && !self.skip_optional
+    }
+}
+
+/// Parse llms.txt content into a structured document
 /// 
 /// This function processes the content of an llms.txt file and extracts
-/// all file paths, converting relative paths to absolute URLs using the base URL.
+/// the title, summary, details and sections, converting relative paths
+/// to absolute URLs using the base URL.
 /// Supports both plain file paths and markdown-style links.
 /// 
 /// # Arguments
@@ -12,9 +135,11 @@ use url::Url;
 /// * `base_url` - The base URL to resolve relative paths against
 /// 
 /// # Returns
-/// * `Result<Vec<String>, DownloadError>` - Vector of absolute URLs or error
-pub fn parse_llms_txt(content: &str, base_url: &str) -> Result<Vec<String>, DownloadError> {
-    let mut file_urls = Vec::new();
+/// * `Result<LlmsTxtDocument, DownloadError>` - The parsed document or error
+pub fn parse_llms_txt(content: &str, base_url: &str) -> Result<LlmsTxtDocument, DownloadError> {
+    let mut document = LlmsTxtDocument::default();
+    let mut summary_lines: Vec<&str> = Vec::new();
+    let mut details_lines: Vec<&str> = Vec::new();
     
     // Validate base URL
     let base = Url::parse(base_url)
@@ -22,24 +147,79 @@ pub fn parse_llms_txt(content: &str, base_url: &str) -> Result<Vec<String>, Down
     
     for line in content.lines() {
         let trimmed = line.trim();
+        // Entries listed before the first H2 heading do not end the preamble
+        let in_preamble = document.sections.iter().all(|section| section.name.is_none());
         
-        // Skip empty lines and comments (lines starting with #)
-        if trimmed.is_empty() || trimmed.starts_with('#') {
+        // H2 headings start a new section
+        if let Some(name) = trimmed.strip_prefix("## ") {
+            document.sections.push(LlmsTxtSection {
+                name: Some(name.trim().to_string()),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        
+        // The first H1 heading is the document title
+        if let Some(title) = trimmed.strip_prefix("# ") {
+            if document.title.is_none() && in_preamble {
+                document.title = Some(title.trim().to_string());
+            }
             continue;
         }
         
-        // Extract file path from the line (handle markdown links and plain paths)
-        if let Some(file_path) = extract_file_path(trimmed) {
+        // Skip other headings and comments (lines starting with #)
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        
+        if trimmed.is_empty() {
+            // Keep paragraph breaks inside the details block
+            if in_preamble && !details_lines.is_empty() {
+                details_lines.push("");
+            }
+            continue;
+        }
+        
+        // A blockquote before any details is the summary
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            if in_preamble && details_lines.is_empty() && document.entry_count() == 0 {
+                summary_lines.push(quote.trim());
+                continue;
+            }
+        }
+        
+        // Extract the entry from the line (handle markdown links and plain paths)
+        if let Some(entry) = extract_entry(trimmed) {
             // Resolve the URL (convert relative to absolute if needed)
-            let resolved_url = resolve_url(&file_path, &base)?;
-            file_urls.push(resolved_url);
+            let url = resolve_url(&entry.url, &base)?;
+            if document.sections.is_empty() {
+                document.sections.push(LlmsTxtSection::default());
+            }
+            if let Some(section) = document.sections.last_mut() {
+                section.entries.push(LlmsTxtEntry { url, ..entry });
+            }
+            continue;
         }
+        
+        // Anything else before the first section is free-form details
+        if in_preamble {
+            details_lines.push(trimmed);
+        }
+    }
+    
+    if !summary_lines.is_empty() {
+        document.summary = Some(summary_lines.join(" "));
+    }
+    
+    let details = details_lines.join("\n").trim().to_string();
+    if !details.is_empty() {
+        document.details = Some(details);
     }
     
-    Ok(file_urls)
+    Ok(document)
 }
 
-/// Extract file path from a line, handling various formats
+/// Extract a file entry from a line, handling various formats
 /// 
 /// Supports:
 /// - Plain file paths: `docs/api.md`
@@ -47,11 +227,11 @@ pub fn parse_llms_txt(content: &str, base_url: &str) -> Result<Vec<String>, Down
 /// - Markdown links with descriptions: `- [Title](/docs/api.md): Description`
 /// 
 /// # Arguments
-/// * `line` - The line to extract the file path from
+/// * `line` - The line to extract the entry from
 /// 
 /// # Returns
-/// * `Option<String>` - The extracted file path, or None if no valid path found
-fn extract_file_path(line: &str) -> Option<String> {
+/// * `Option<LlmsTxtEntry>` - The extracted entry with an unresolved URL, or None if no valid path found
+fn extract_entry(line: &str) -> Option<LlmsTxtEntry> {
     let trimmed = line.trim();
     
     // Handle markdown-style links: - [Title](/path.md) or - [Title](/path.md): Description
@@ -60,19 +240,163 @@ fn extract_file_path(line: &str) -> Option<String> {
             let path = &trimmed[start + 2..start + 2 + end];
             // Only return paths that look like file paths (contain . or end with .md)
             if path.contains('.') || path.ends_with(".md") {
-                return Some(path.to_string());
+                let title = trimmed[..start]
+                    .rfind('[')
+                    .map(|open| trimmed[open + 1..start].trim().to_string())
+                    .filter(|title| !title.is_empty());
+                let description = trimmed[start + 2 + end + 1..]
+                    .trim()
+                    .trim_start_matches(':')
+                    .trim();
+                
+                return Some(LlmsTxtEntry {
+                    title,
+                    url: path.to_string(),
+                    description: (!description.is_empty()).then(|| description.to_string()),
+                });
             }
         }
     }
     
-    // Handle plain file paths (must contain a dot to be considered a file)
-    if trimmed.contains('.') && !trimmed.starts_with('-') {
-        return Some(trimmed.to_string());
+    // Handle plain file paths (must contain a dot to be considered a file,
+    // prose containing whitespace belongs to the details instead)
+    if trimmed.contains('.') && !trimmed.starts_with('-') && !trimmed.contains(char::is_whitespace) {
+        return Some(LlmsTxtEntry {
+            title: None,
+            url: trimmed.to_string(),
+            description: None,
+        });
     }
     
     None
 }
 
+/// Tracks the fenced code blocks of markdown content, line by line
+/// 
+/// Follows CommonMark: a fence is a run of at least three backticks or
+/// tildes indented by at most three spaces, and a code block is only closed
+/// by a fence of the same character that is at least as long as the one
+/// that opened it, with nothing but spaces after it.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CodeFences {
+    /// Character and length of the fence that opened the current code block
+    open: Option<(char, usize)>,
+}
+
+impl CodeFences {
+    /// Check if a line opens or closes a code block, and track the change
+    /// 
+    /// Call this for every line in order, then [`CodeFences::in_code_block`]
+    /// tells whether the other lines are code.
+    pub(crate) fn is_fence(&mut self, line: &str) -> bool {
+        let Some((marker, length, rest)) = fence_run(line) else {
+            return false;
+        };
+        match self.open {
+            Some((open_marker, open_length)) => {
+                let closes = marker == open_marker && length >= open_length && rest.trim().is_empty();
+                if closes {
+                    self.open = None;
+                }
+                closes
+            }
+            // The info string of a backtick fence cannot contain backticks
+            None if marker == '`' && rest.contains('`') => false,
+            None => {
+                self.open = Some((marker, length));
+                true
+            }
+        }
+    }
+
+    /// Check if the lines after the last fence are inside a code block
+    pub(crate) fn in_code_block(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
+/// Split a fence line into its character, the length of the run and the rest
+fn fence_run(line: &str) -> Option<(char, usize, &str)> {
+    let unindented = line.trim_start_matches(' ');
+    // Lines indented by four spaces are indented code
+    if line.len() - unindented.len() > 3 {
+        return None;
+    }
+    let marker = unindented.chars().next().filter(|marker| matches!(marker, '`' | '~'))?;
+    let rest = unindented.trim_start_matches(marker);
+    let length = unindented.len() - rest.len();
+    (length >= 3).then_some((marker, length, rest))
+}
+
+/// A single document extracted from an llms-full.txt file
+#[derive(Debug, Clone)]
+pub struct FullTextDocument {
+    /// Title from the H1 heading that starts the document
+    pub title: Option<String>,
+    /// Original URL of the document from a `Source:` or `URL:` line, if present
+    pub source_url: Option<String>,
+    /// Markdown content of the document, including its heading
+    pub content: String,
+}
+
+/// Split llms-full.txt content back into the documents it concatenates
+/// 
+/// Every H1 heading outside a fenced code block starts a new document.
+/// Many generators emit a `Source: <url>` line right below the heading,
+/// which is resolved against the base URL and kept as the document origin.
+/// 
+/// # Arguments
+/// * `content` - The raw content of the llms-full.txt file
+/// * `base_url` - The base URL to resolve relative source URLs against
+/// 
+/// # Returns
+/// * `Result<Vec<FullTextDocument>, DownloadError>` - The documents in file order or error
+pub fn split_llms_full(content: &str, base_url: &str) -> Result<Vec<FullTextDocument>, DownloadError> {
+    // Validate base URL
+    let base = Url::parse(base_url)
+        .map_err(|e| DownloadError::InvalidUrl(format!("Invalid base URL '{base_url}': {e}")))?;
+    
+    // Group lines by the H1 heading they belong to
+    let mut parts: Vec<(Option<&str>, Vec<&str>)> = vec![(None, Vec::new())];
+    let mut fences = CodeFences::default();
+    
+    for line in content.lines() {
+        if !fences.is_fence(line) && !fences.in_code_block() {
+            if let Some(title) = line.strip_prefix("# ") {
+                parts.push((Some(title.trim()), Vec::new()));
+            }
+        }
+        
+        if let Some((_, lines)) = parts.last_mut() {
+            lines.push(line);
+        }
+    }
+    
+    let documents = parts
+        .into_iter()
+        .filter(|(_, lines)| lines.iter().any(|line| !line.trim().is_empty()))
+        .map(|(title, lines)| {
+            // Look for the source line among the first lines below the heading
+            let source_url = lines
+                .iter()
+                .skip(1)
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .take(3)
+                .find_map(|line| line.strip_prefix("Source:").or_else(|| line.strip_prefix("URL:")))
+                .and_then(|source| resolve_url(source.trim(), &base).ok());
+            
+            FullTextDocument {
+                title: title.map(str::to_string),
+                source_url,
+                content: format!("{}\n", lines.join("\n").trim_end()),
+            }
+        })
+        .collect();
+    
+    Ok(documents)
+}
+
 /// Convert relative paths to absolute URLs using the base URL
 /// 
 /// This function takes a file path (which can be relative or absolute)
@@ -97,3 +421,211 @@ pub fn resolve_url(path: &str, base: &Url) -> Result<String, DownloadError> {
     Ok(resolved.to_string())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const LLMS_TXT: &str = "\
+# Example
+
+> A project
+> with a summary
+
+Some details.
+
+More details.
+
+## Docs
+
+- [Guide](guide.md): How to start
+- [API](/api/index.md)
+- [About](/about)
+
+## Optional
+
+- [Changelog](https://other.example/changelog.md)
+";
+
+    fn parse(content: &str) -> LlmsTxtDocument {
+        parse_llms_txt(content, "https://example.com/docs/llms.txt").unwrap()
+    }
+
+    fn urls(document: &LlmsTxtDocument) -> Vec<&str> {
+        document.entries().map(|entry| entry.url.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_title_summary_and_details() {
+        let document = parse(LLMS_TXT);
+        
+        assert_eq!(document.title.as_deref(), Some("Example"));
+        assert_eq!(document.summary.as_deref(), Some("A project with a summary"));
+        assert_eq!(document.details.as_deref(), Some("Some details.\n\nMore details."));
+    }
+
+    #[test]
+    fn parses_sections_and_resolves_urls() {
+        let document = parse(LLMS_TXT);
+        
+        let names: Vec<_> = document.sections.iter().map(|section| section.name.as_deref()).collect();
+        assert_eq!(names, [Some("Docs"), Some("Optional")]);
+        assert_eq!(
+            urls(&document),
+            [
+                "https://example.com/docs/guide.md",
+                "https://example.com/api/index.md",
+                "https://other.example/changelog.md",
+            ]
+        );
+        
+        let guide = &document.sections[0].entries[0];
+        assert_eq!(guide.title.as_deref(), Some("Guide"));
+        assert_eq!(guide.description.as_deref(), Some("How to start"));
+    }
+
+    #[test]
+    fn keeps_entries_before_the_first_section() {
+        let document = parse("# Example\n\nintro.md\n\n## Docs\n\n- [A](a.md)\n");
+        
+        assert_eq!(document.sections[0].name, None);
+        assert_eq!(urls(&document), ["https://example.com/docs/intro.md", "https://example.com/docs/a.md"]);
+        assert_eq!(document.details, None);
+    }
+
+    #[test]
+    fn keeps_details_after_entries_before_the_first_section() {
+        let document = parse("# Example\n\n- [Intro](intro.md)\n\nDetails after the entry.\n\n## Docs\n\nNot details.\n\n- [A](a.md)\n");
+        
+        assert_eq!(document.details.as_deref(), Some("Details after the entry."));
+        assert_eq!(urls(&document), ["https://example.com/docs/intro.md", "https://example.com/docs/a.md"]);
+    }
+
+    #[test]
+    fn rejects_an_invalid_base_url() {
+        assert!(matches!(parse_llms_txt(LLMS_TXT, "not a url"), Err(DownloadError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn skip_optional_drops_the_optional_section() {
+        let mut document = parse(LLMS_TXT.replace("## Optional", "## optional").as_str());
+        let filter = SectionFilter {
+            skip_optional: true,
+            ..SectionFilter::default()
+        };
+        
+        assert_eq!(document.apply_filter(&filter), 1);
+        assert_eq!(document.sections.len(), 1);
+        assert_eq!(document.sections[0].name.as_deref(), Some("Docs"));
+    }
+
+    #[test]
+    fn include_and_exclude_match_section_names_ignoring_case() {
+        let include = SectionFilter {
+            include: vec!["OPTIONAL".to_string()],
+            ..SectionFilter::default()
+        };
+        let mut document = parse(LLMS_TXT);
+        assert_eq!(document.apply_filter(&include), 2);
+        assert_eq!(urls(&document), ["https://other.example/changelog.md"]);
+        
+        let exclude = SectionFilter {
+            exclude: vec![" docs ".to_string()],
+            ..SectionFilter::default()
+        };
+        let mut document = parse(LLMS_TXT);
+        assert_eq!(document.apply_filter(&exclude), 2);
+        assert_eq!(urls(&document), ["https://other.example/changelog.md"]);
+    }
+
+    #[test]
+    fn include_drops_entries_without_a_section() {
+        let filter = SectionFilter {
+            include: vec!["Docs".to_string()],
+            ..SectionFilter::default()
+        };
+        assert!(!filter.allows(None));
+        assert!(SectionFilter::default().allows(None));
+        assert!(SectionFilter::default().is_empty());
+    }
+
+    #[test]
+    fn split_llms_full_splits_at_h1_headings() {
+        let content = "\
+Preamble text
+
+# First
+
+Source: /docs/first.md
+
+Body of the first document.
+
+# Second
+URL: https://example.com/second.md
+Body.
+";
+        let documents = split_llms_full(content, "https://example.com/llms-full.txt").unwrap();
+        
+        let titles: Vec<_> = documents.iter().map(|document| document.title.as_deref()).collect();
+        assert_eq!(titles, [None, Some("First"), Some("Second")]);
+        assert_eq!(documents[0].content, "Preamble text\n");
+        assert_eq!(documents[1].source_url.as_deref(), Some("https://example.com/docs/first.md"));
+        assert_eq!(documents[1].content, "# First\n\nSource: /docs/first.md\n\nBody of the first document.\n");
+        assert_eq!(documents[2].source_url.as_deref(), Some("https://example.com/second.md"));
+    }
+
+    #[test]
+    fn split_llms_full_ignores_headings_in_code_blocks() {
+        let content = "# Shell\n\n```sh\n# not a heading\n```\n\n~~~\n# nor this\n~~~\n";
+        let documents = split_llms_full(content, "https://example.com/").unwrap();
+        
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].title.as_deref(), Some("Shell"));
+        assert_eq!(documents[0].source_url, None);
+        assert!(documents[0].content.contains("# nor this"));
+    }
+
+    #[test]
+    fn split_llms_full_closes_code_blocks_with_matching_fences() {
+        // A tilde fence does not close a backtick block, nor a shorter backtick fence a longer one
+        let content = "# Doc\n\n````md\n~~~\n# not a heading\n```\n# nor this\n````\n\n# Next\n";
+        let documents = split_llms_full(content, "https://example.com/").unwrap();
+        
+        let titles: Vec<_> = documents.iter().map(|document| document.title.as_deref()).collect();
+        assert_eq!(titles, [Some("Doc"), Some("Next")]);
+        assert!(documents[0].content.contains("# nor this"));
+    }
+
+    #[test]
+    fn code_fences_follow_commonmark() {
+        let mut fences = CodeFences::default();
+        assert!(!fences.is_fence("    ```"));
+        assert!(!fences.is_fence("``"));
+        assert!(!fences.is_fence("``` a`b"));
+        assert!(!fences.in_code_block());
+        
+        assert!(fences.is_fence("   ~~~~ rust"));
+        assert!(!fences.is_fence("~~~"));
+        assert!(!fences.is_fence("~~~~~ rust"));
+        assert!(!fences.is_fence("````"));
+        assert!(fences.in_code_block());
+        assert!(fences.is_fence("~~~~~  "));
+        assert!(!fences.in_code_block());
+    }
+
+    #[test]
+    fn split_llms_full_only_looks_for_sources_below_the_heading() {
+        let content = "# Doc\n\nOne\n\nTwo\n\nThree\n\nSource: /late.md\n";
+        let documents = split_llms_full(content, "https://example.com/").unwrap();
+        
+        assert_eq!(documents[0].source_url, None);
+    }
+
+    #[test]
+    fn split_llms_full_skips_blank_parts() {
+        let documents = split_llms_full("\n\n# Only\nText\n", "https://example.com/").unwrap();
+        
+        assert_eq!(documents.len(), 1);
+        assert!(split_llms_full("", "https://example.com/").unwrap().is_empty());
+    }
+}
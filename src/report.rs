@@ -0,0 +1,473 @@
+use crate::error::{BatchResult, DownloadError, DownloadFailure, DownloadResult, PlannedFile, SkippedFile};
+use crate::sync::Changes;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Format of the end-of-run report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A single JSON object
+    Json,
+    /// JUnit XML with one test case per file, for CI test result viewers
+    Junit,
+    /// Markdown tables, e.g. for CI job summaries
+    Markdown,
+}
+
+/// Renders the end-of-run report for a website
+/// 
+/// # Arguments
+/// * `url` - The website the run was for
+/// * `outcome` - The result of the run, or the error that stopped it before any file was downloaded
+/// * `format` - The report format
+/// 
+/// # Returns
+/// * `String` - The report, ending with a newline
+pub fn render_report(url: &str, outcome: Result<&DownloadResult, &DownloadError>, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => render_json(url, outcome),
+        ReportFormat::Junit => render_junit(url, outcome),
+        ReportFormat::Markdown => render_markdown(url, outcome),
+    }
+}
+
+/// Renders the end-of-run report for several websites
+/// 
+/// JSON reports list every website under `sites` with a combined summary,
+/// JUnit XML has one test suite per website and Markdown one section per
+/// website after a per-domain overview.
+/// 
+/// # Arguments
+/// * `batch` - The results of all websites
+/// * `format` - The report format
+/// 
+/// # Returns
+/// * `String` - The report, ending with a newline
+pub fn render_batch_report(batch: &BatchResult, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => render_batch_json(batch),
+        ReportFormat::Junit => render_batch_junit(batch),
+        ReportFormat::Markdown => render_batch_markdown(batch),
+    }
+}
+
+/// Overall status of a batch, like [`run_status`] with failed websites counting as failures
+fn batch_status(batch: &BatchResult) -> &'static str {
+    let combined = batch.combined();
+    match batch.failed_site_count() {
+        0 => run_status(Ok(&combined)),
+        failed if failed == batch.sites.len() => "error",
+        _ if combined.completed_count() > 0 => "partial",
+        _ => "failed",
+    }
+}
+
+/// Overall status of a run: `success`, `partial`, `skipped`, `failed`, `error`
+/// or `dry-run`
+fn run_status(outcome: Result<&DownloadResult, &DownloadError>) -> &'static str {
+    match outcome {
+        Ok(result) if result.changes.as_ref().is_some_and(|changes| changes.dry_run) => "dry-run",
+        Ok(result) if result.all_successful() => "success",
+        Ok(result) if result.completed_count() > 0 => "partial",
+        // Every file was skipped by policy, none failed
+        Ok(result) if result.failed.is_empty() && !result.skipped.is_empty() => "skipped",
+        Ok(_) => "failed",
+        Err(_) => "error",
+    }
+}
+
+/// Share of files that are up to date locally, in percent
+fn success_rate(result: &DownloadResult) -> f64 {
+    if result.total_files == 0 {
+        return 0.0;
+    }
+    (result.completed_count() as f64 / result.total_files as f64) * 100.0
+}
+
+/// JSON report layout
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    url: &'a str,
+    status: &'static str,
+    error: Option<String>,
+    summary: Option<JsonSummary>,
+    successful: Vec<JsonFile<'a>>,
+    unchanged: Vec<JsonFile<'a>>,
+    failed: Vec<&'a DownloadFailure>,
+    skipped: Vec<&'a SkippedFile>,
+    changes: Option<&'a Changes>,
+    planned: Vec<&'a PlannedFile>,
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    total_files: usize,
+    successful: usize,
+    unchanged: usize,
+    failed: usize,
+    skipped: usize,
+    total_bytes: u64,
+    resumed_bytes: u64,
+    duration_secs: f64,
+    success_rate: f64,
+}
+
+#[derive(Serialize)]
+struct JsonFile<'a> {
+    url: &'a str,
+    path: &'a str,
+}
+
+fn render_json(url: &str, outcome: Result<&DownloadResult, &DownloadError>) -> String {
+    to_json(&json_report(url, outcome))
+}
+
+fn json_report<'a>(url: &'a str, outcome: Result<&'a DownloadResult, &DownloadError>) -> JsonReport<'a> {
+    match outcome {
+        Ok(result) => JsonReport {
+            url,
+            status: run_status(outcome),
+            error: None,
+            summary: Some(json_summary(result)),
+            successful: json_files(&result.successful),
+            unchanged: json_files(&result.unchanged),
+            failed: result.failed.iter().collect(),
+            skipped: result.skipped.iter().collect(),
+            changes: result.changes.as_ref(),
+            planned: result.planned.iter().collect(),
+        },
+        Err(e) => JsonReport {
+            url,
+            status: run_status(outcome),
+            error: Some(e.to_string()),
+            summary: None,
+            successful: Vec::new(),
+            unchanged: Vec::new(),
+            failed: Vec::new(),
+            skipped: Vec::new(),
+            changes: None,
+            planned: Vec::new(),
+        },
+    }
+}
+
+fn json_summary(result: &DownloadResult) -> JsonSummary {
+    JsonSummary {
+        total_files: result.total_files,
+        successful: result.success_count(),
+        unchanged: result.unchanged_count(),
+        failed: result.failure_count(),
+        skipped: result.skipped_count(),
+        total_bytes: result.total_bytes,
+        resumed_bytes: result.resumed_bytes,
+        duration_secs: result.duration().as_secs_f64(),
+        success_rate: success_rate(result),
+    }
+}
+
+/// JSON report layout for several websites
+#[derive(Serialize)]
+struct JsonBatchReport<'a> {
+    status: &'static str,
+    sites_total: usize,
+    sites_failed: usize,
+    summary: JsonSummary,
+    sites: Vec<JsonReport<'a>>,
+}
+
+fn render_batch_json(batch: &BatchResult) -> String {
+    let report = JsonBatchReport {
+        status: batch_status(batch),
+        sites_total: batch.sites.len(),
+        sites_failed: batch.failed_site_count(),
+        summary: json_summary(&batch.combined()),
+        sites: batch
+            .sites
+            .iter()
+            .map(|site| json_report(&site.url, site.outcome.as_ref()))
+            .collect(),
+    };
+    to_json(&report)
+}
+
+fn to_json(report: &impl Serialize) -> String {
+    let mut json = serde_json::to_string_pretty(report).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+fn json_files(files: &[(String, String)]) -> Vec<JsonFile<'_>> {
+    files.iter().map(|(url, path)| JsonFile { url, path }).collect()
+}
+
+fn render_junit(url: &str, outcome: Result<&DownloadResult, &DownloadError>) -> String {
+    let suite = junit_suite(url, outcome);
+    junit_document(&suite.xml, suite.tests, suite.failures, suite.errors, suite.time)
+}
+
+fn render_batch_junit(batch: &BatchResult) -> String {
+    let suites: Vec<JunitSuite> = batch.sites.iter().map(|site| junit_suite(&site.url, site.outcome.as_ref())).collect();
+    let xml: String = suites.iter().map(|suite| suite.xml.as_str()).collect();
+    junit_document(
+        &xml,
+        suites.iter().map(|suite| suite.tests).sum(),
+        suites.iter().map(|suite| suite.failures).sum(),
+        suites.iter().map(|suite| suite.errors).sum(),
+        batch.start_time.elapsed().as_secs_f64(),
+    )
+}
+
+/// A `<testsuite>` element and its counts
+struct JunitSuite {
+    xml: String,
+    tests: usize,
+    failures: usize,
+    errors: usize,
+    time: f64,
+}
+
+fn junit_document(suites: &str, tests: usize, failures: usize, errors: usize, time: f64) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"llmsdl\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{time:.3}\">\n\
+         {suites}\
+         </testsuites>\n"
+    )
+}
+
+fn junit_suite(url: &str, outcome: Result<&DownloadResult, &DownloadError>) -> JunitSuite {
+    let site = escape_xml(url);
+    let mut cases = String::new();
+    let (tests, failures, errors, skipped, time) = match outcome {
+        Ok(result) => {
+            for (file_url, path) in &result.successful {
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{site}\" name=\"{}\">\n      <system-out>downloaded to {}</system-out>\n    </testcase>",
+                    escape_xml(file_url),
+                    escape_xml(path)
+                );
+            }
+            for (file_url, path) in &result.unchanged {
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{site}\" name=\"{}\">\n      <system-out>unchanged at {}</system-out>\n    </testcase>",
+                    escape_xml(file_url),
+                    escape_xml(path)
+                );
+            }
+            for failure in &result.failed {
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{site}\" name=\"{}\">\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>",
+                    escape_xml(&failure.url),
+                    failure.kind,
+                    escape_xml(&failure.error),
+                    escape_xml(&failure.sources.join("\n"))
+                );
+            }
+            for skipped in &result.skipped {
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{site}\" name=\"{}\">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                    escape_xml(&skipped.url),
+                    escape_xml(&skipped.reason)
+                );
+            }
+            let tests = result.total_files + result.skipped_count();
+            (tests, result.failure_count(), 0, result.skipped_count(), result.duration().as_secs_f64())
+        }
+        Err(e) => {
+            let _ = writeln!(
+                cases,
+                "    <testcase classname=\"{site}\" name=\"llms.txt\">\n      <error type=\"error\" message=\"{}\"/>\n    </testcase>",
+                escape_xml(&e.to_string())
+            );
+            (1, 0, 1, 0, 0.0)
+        }
+    };
+    
+    let xml = format!(
+        "  <testsuite name=\"{site}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\" time=\"{time:.3}\">\n\
+         {cases}  </testsuite>\n"
+    );
+    JunitSuite { xml, tests, failures, errors, time }
+}
+
+fn render_markdown(url: &str, outcome: Result<&DownloadResult, &DownloadError>) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "# llmsdl report: {url}\n");
+    
+    let result = match outcome {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = writeln!(report, "❌ **Error:** {}", escape_markdown(&e.to_string()));
+            return report;
+        }
+    };
+    
+    let _ = writeln!(report, "**Status:** {}\n", run_status(outcome));
+    let _ = writeln!(report, "| | Count |");
+    let _ = writeln!(report, "|---|---:|");
+    let _ = writeln!(report, "| ✅ Downloaded | {} |", result.success_count());
+    let _ = writeln!(report, "| ♻️ Unchanged | {} |", result.unchanged_count());
+    let _ = writeln!(report, "| ❌ Failed | {} |", result.failure_count());
+    if !result.skipped.is_empty() {
+        let _ = writeln!(report, "| 🚫 Skipped by policy | {} |", result.skipped_count());
+    }
+    let _ = writeln!(report, "| 💾 Data downloaded | {} |", DownloadResult::format_bytes(result.total_bytes));
+    let _ = writeln!(report, "| 📈 Success rate | {:.1}% |", success_rate(result));
+    let _ = writeln!(report, "| ⏱️ Duration | {:.2}s |", result.duration().as_secs_f64());
+    
+    if let Some(changes) = &result.changes {
+        let _ = writeln!(report, "\n## Changes since the last run\n");
+        let _ = writeln!(report, "| Added | Updated | Unchanged | Removed |");
+        let _ = writeln!(report, "|---:|---:|---:|---:|");
+        let _ = writeln!(
+            report,
+            "| {} | {} | {} | {} |",
+            changes.added.len(),
+            changes.updated.len(),
+            changes.unchanged.len(),
+            changes.removed.len()
+        );
+        if !changes.removed.is_empty() {
+            let _ = writeln!(report, "\nNo longer listed, {}:\n", changes.removed_action());
+            let _ = writeln!(report, "| Path | URL |");
+            let _ = writeln!(report, "|---|---|");
+            for file in &changes.removed {
+                let _ = writeln!(report, "| {} | {} |", escape_markdown(&file.path), escape_markdown(&file.url));
+            }
+        }
+    }
+    
+    if !result.failed.is_empty() {
+        let _ = writeln!(report, "\n## Failed files\n");
+        let _ = writeln!(report, "| URL | Kind | Error |");
+        let _ = writeln!(report, "|---|---|---|");
+        for failure in &result.failed {
+            let mut error = escape_markdown(&failure.error);
+            for source in &failure.sources {
+                error.push_str("<br>caused by: ");
+                error.push_str(&escape_markdown(source));
+            }
+            let _ = writeln!(
+                report,
+                "| {} | {} | {error} |",
+                escape_markdown(&failure.url),
+                failure.kind
+            );
+        }
+    }
+    
+    if !result.skipped.is_empty() {
+        let _ = writeln!(report, "\n## Skipped files\n");
+        let _ = writeln!(report, "| URL | Reason |");
+        let _ = writeln!(report, "|---|---|");
+        for skipped in &result.skipped {
+            let _ = writeln!(report, "| {} | {} |", escape_markdown(&skipped.url), escape_markdown(&skipped.reason));
+        }
+    }
+    
+    if !result.planned.is_empty() {
+        let _ = writeln!(report, "\n## Planned files\n");
+        let _ = writeln!(report, "| Section | Title | URL | Path |");
+        let _ = writeln!(report, "|---|---|---|---|");
+        for file in &result.planned {
+            let _ = writeln!(
+                report,
+                "| {} | {} | {} | {} |",
+                escape_markdown(file.section.as_deref().unwrap_or_default()),
+                escape_markdown(file.title.as_deref().unwrap_or_default()),
+                escape_markdown(&file.url),
+                escape_markdown(&file.path)
+            );
+        }
+    }
+    
+    if !result.successful.is_empty() {
+        let _ = writeln!(report, "\n## Downloaded files\n");
+        let _ = writeln!(report, "| URL | Path |");
+        let _ = writeln!(report, "|---|---|");
+        for (file_url, path) in &result.successful {
+            let _ = writeln!(report, "| {} | {} |", escape_markdown(file_url), escape_markdown(path));
+        }
+    }
+    
+    report
+}
+
+fn render_batch_markdown(batch: &BatchResult) -> String {
+    let mut report = String::new();
+    let combined = batch.combined();
+    let _ = writeln!(report, "# llmsdl report: {} sites\n", batch.sites.len());
+    let _ = writeln!(report, "**Status:** {}\n", batch_status(batch));
+    let _ = writeln!(report, "| Site | Status | Downloaded | Unchanged | Failed | Skipped | Data |");
+    let _ = writeln!(report, "|---|---|---:|---:|---:|---:|---:|");
+    for site in &batch.sites {
+        match &site.outcome {
+            Ok(result) => {
+                let _ = writeln!(
+                    report,
+                    "| {} | {} | {} | {} | {} | {} | {} |",
+                    escape_markdown(&site.domain()),
+                    run_status(Ok(result)),
+                    result.success_count(),
+                    result.unchanged_count(),
+                    result.failure_count(),
+                    result.skipped_count(),
+                    DownloadResult::format_bytes(result.total_bytes)
+                );
+            }
+            Err(_) => {
+                let _ = writeln!(report, "| {} | error | | | | | |", escape_markdown(&site.domain()));
+            }
+        }
+    }
+    let _ = writeln!(
+        report,
+        "| **Total** | | {} | {} | {} | {} | {} |",
+        combined.success_count(),
+        combined.unchanged_count(),
+        combined.failure_count(),
+        combined.skipped_count(),
+        DownloadResult::format_bytes(combined.total_bytes)
+    );
+    let _ = writeln!(report, "\n⏱️ Duration: {:.2}s", batch.start_time.elapsed().as_secs_f64());
+    
+    // One section per website, one heading level down
+    for site in &batch.sites {
+        let section = render_markdown(&site.url, site.outcome.as_ref());
+        for line in section.lines() {
+            if line.starts_with('#') {
+                report.push('#');
+            }
+            report.push_str(line);
+            report.push('\n');
+        }
+    }
+    report
+}
+
+/// Escapes text for use in XML attributes and elements
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            ch if ch.is_control() => {}
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Escapes text for use in a Markdown table cell
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
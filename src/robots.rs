@@ -0,0 +1,310 @@
+use std::time::Duration;
+
+/// The rules of a robots.txt file that apply to one user agent
+/// 
+/// Follows RFC 9309: the groups naming the user agent's product token are
+/// used, or the `*` group when none does. The longest matching `Allow` or
+/// `Disallow` pattern decides, with `Allow` winning ties. Patterns may use
+/// `*` wildcards and a trailing `$` anchor. Patterns and paths are compared
+/// after normalizing their percent-encoding. The non-standard `Crawl-delay`
+/// directive is read as well.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsTxt {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// A group of rules and the user agents it applies to
+#[derive(Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsTxt {
+    /// Parse a robots.txt file, keeping the rules for a user agent
+    /// 
+    /// # Arguments
+    /// * `content` - The content of the robots.txt file
+    /// * `user_agent` - The `User-Agent` header sent by the client, e.g. `llmsdl/0.1.0`
+    /// 
+    /// # Returns
+    /// * `RobotsTxt` - The rules that apply to the user agent
+    pub fn parse(content: &str, user_agent: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        let mut in_agent_lines = false;
+        
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // Consecutive user-agent lines share one group
+                    if !in_agent_lines {
+                        groups.push(Group::default());
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                    in_agent_lines = true;
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agent_lines = false;
+                    // An empty Disallow allows everything, so it adds no rule
+                    if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                        group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: normalize_encoding(value),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    in_agent_lines = false;
+                    let delay = value.parse::<f64>().ok().filter(|delay| delay.is_finite() && *delay >= 0.0);
+                    if let (Some(group), Some(delay)) = (groups.last_mut(), delay) {
+                        group.crawl_delay = Some(Duration::from_secs_f64(delay));
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        let token = product_token(user_agent);
+        let matches_agent = |group: &Group| group.agents.contains(&token);
+        let matches_any = |group: &Group| group.agents.iter().any(|agent| agent == "*");
+        
+        // Rules of all matching groups are combined
+        let selected: Vec<Group> = if groups.iter().any(matches_agent) {
+            groups.into_iter().filter(matches_agent).collect()
+        } else {
+            groups.into_iter().filter(matches_any).collect()
+        };
+        
+        let mut robots = Self::default();
+        for group in selected {
+            robots.rules.extend(group.rules);
+            robots.crawl_delay = robots.crawl_delay.max(group.crawl_delay);
+        }
+        robots
+    }
+
+    /// A robots.txt that disallows everything
+    /// 
+    /// Used when robots.txt could not be fetched because of a server or
+    /// network error, as RFC 9309 requires.
+    pub fn disallow_all() -> Self {
+        Self {
+            rules: vec![Rule {
+                allow: false,
+                pattern: "/".to_string(),
+            }],
+            crawl_delay: None,
+        }
+    }
+
+    /// Check if a path (with query string) may be fetched
+    pub fn is_allowed(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        
+        let path = normalize_encoding(path);
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, &path))
+            // Longest pattern wins, Allow wins ties
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// Get the requested delay between two requests, if any
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// Get the lowercase product token of a user agent, e.g. `llmsdl` for `llmsdl/0.1.0`
+fn product_token(user_agent: &str) -> String {
+    user_agent
+        .split(['/', ' '])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Bring the percent-encoding of a pattern or path into one form (RFC 9309 §2.2.2)
+/// 
+/// Encoded unreserved characters are decoded, other escapes get uppercase
+/// hex digits and characters outside of printable US-ASCII are encoded, so
+/// `/%7Efoo/%e3%83%84` and `/~foo/ツ` compare equal. Reserved characters
+/// such as `%2F` stay encoded, since decoding them would change the path.
+fn normalize_encoding(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut normalized = String::with_capacity(value.len());
+    let mut index = 0;
+    
+    while index < bytes.len() {
+        let byte = bytes[index];
+        let escaped = (byte == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|digit| digit.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        
+        match escaped {
+            Some(decoded) if decoded.is_ascii_alphanumeric() || b"-._~".contains(&decoded) => {
+                normalized.push(char::from(decoded));
+                index += 3;
+            }
+            Some(decoded) => {
+                normalized.push_str(&format!("%{decoded:02X}"));
+                index += 3;
+            }
+            None if byte.is_ascii_graphic() => {
+                normalized.push(char::from(byte));
+                index += 1;
+            }
+            None => {
+                normalized.push_str(&format!("%{byte:02X}"));
+                index += 1;
+            }
+        }
+    }
+    normalized
+}
+
+/// Match a robots.txt pattern against the start of a path
+/// 
+/// `*` matches any sequence of characters and a trailing `$` anchors the
+/// pattern at the end of the path.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        let is_last = index == parts.len() - 1;
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn robots(content: &str) -> RobotsTxt {
+        RobotsTxt::parse(content, "llmsdl/0.1.0")
+    }
+
+    #[test]
+    fn longest_match_wins() {
+        let robots = robots("User-agent: *\nDisallow: /docs/\nAllow: /docs/public/\nDisallow: /docs/public/secret\n");
+        
+        assert!(robots.is_allowed("/"));
+        assert!(!robots.is_allowed("/docs/a.md"));
+        assert!(robots.is_allowed("/docs/public/a.md"));
+        assert!(!robots.is_allowed("/docs/public/secret.md"));
+    }
+
+    #[test]
+    fn allow_wins_ties() {
+        let robots = robots("User-agent: *\nDisallow: /page\nAllow: /page\n");
+        assert!(robots.is_allowed("/page.md"));
+    }
+
+    #[test]
+    fn wildcards_and_end_anchor() {
+        let robots = robots("User-agent: *\nDisallow: /*.pdf$\nDisallow: /private*/draft\nAllow: /$\n");
+        
+        assert!(!robots.is_allowed("/files/guide.pdf"));
+        assert!(robots.is_allowed("/files/guide.pdf?download=1"));
+        assert!(robots.is_allowed("/files/guide.pdf.md"));
+        assert!(!robots.is_allowed("/private-area/v2/draft.md"));
+        assert!(robots.is_allowed("/"));
+    }
+
+    #[test]
+    fn end_anchor_matches_the_whole_path() {
+        assert!(pattern_matches("/a$", "/a"));
+        assert!(!pattern_matches("/a$", "/ab"));
+        assert!(pattern_matches("/*/b$", "/a/b"));
+        assert!(!pattern_matches("/*/b$", "/a/b/c"));
+        assert!(pattern_matches("*", "/anything"));
+    }
+
+    #[test]
+    fn matching_group_replaces_the_default_group() {
+        let content = "User-agent: *\nDisallow: /\n\nUser-agent: other\nUser-agent: LLMSDL\nDisallow: /private/\nCrawl-delay: 1.5\n";
+        let robots = robots(content);
+        
+        assert!(robots.is_allowed("/docs/a.md"));
+        assert!(!robots.is_allowed("/private/a.md"));
+        assert_eq!(robots.crawl_delay(), Some(Duration::from_millis(1500)));
+        
+        let others = RobotsTxt::parse(content, "curl/8.0");
+        assert!(!others.is_allowed("/docs/a.md"));
+        assert_eq!(others.crawl_delay(), None);
+    }
+
+    #[test]
+    fn empty_disallow_and_comments_allow_everything() {
+        let robots = robots("# Everything is fine\nUser-agent: * # all\nDisallow:\n");
+        assert!(robots.is_allowed("/docs/a.md"));
+        assert!(RobotsTxt::default().is_allowed("/docs/a.md"));
+    }
+
+    #[test]
+    fn robots_txt_itself_is_always_allowed() {
+        let robots = RobotsTxt::disallow_all();
+        assert!(robots.is_allowed("/robots.txt"));
+        assert!(!robots.is_allowed("/llms.txt"));
+    }
+
+    #[test]
+    fn percent_encoding_is_normalized() {
+        let robots = robots("User-agent: *\nDisallow: /a%2fb\nDisallow: /%7Euser/\nDisallow: /caf%C3%A9\nDisallow: /ツ\n");
+        
+        assert!(!robots.is_allowed("/a%2Fb.md"));
+        // An encoded slash is not the same path as a plain one
+        assert!(robots.is_allowed("/a/b.md"));
+        assert!(!robots.is_allowed("/~user/a.md"));
+        assert!(!robots.is_allowed("/café.md"));
+        assert!(!robots.is_allowed("/%E3%83%84/a.md"));
+    }
+
+    #[test]
+    fn normalizes_escapes() {
+        assert_eq!(normalize_encoding("/%62%61%7a"), "/baz");
+        assert_eq!(normalize_encoding("/a%2fb%3F"), "/a%2Fb%3F");
+        assert_eq!(normalize_encoding("/ツ x"), "/%E3%83%84%20x");
+        assert_eq!(normalize_encoding("/100%"), "/100%");
+        assert_eq!(normalize_encoding("/%+1"), "/%+1");
+    }
+}
@@ -0,0 +1,389 @@
+use crate::cache::CACHE_FILE_NAME;
+use crate::error::DownloadError;
+use crate::file_manager::{split_extension, MappingManifest, MAPPING_FILE_NAME};
+use crate::manifest::{Manifest, MANIFEST_FILE_NAME};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory in each domain directory that quarantined files are moved to
+pub const QUARANTINE_DIR: &str = ".llmsdl-removed";
+
+/// What to do with local files whose entry is no longer listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PruneMode {
+    /// Leave them in place, only report them
+    #[default]
+    Keep,
+    /// Delete them
+    Delete,
+    /// Move them to [`QUARANTINE_DIR`], keeping their relative path
+    Quarantine,
+}
+
+/// A document of a run, identified by its URL and local path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedFile {
+    /// URL the file was downloaded from
+    pub url: String,
+    /// Path relative to the domain directory, with `/` separators
+    pub path: String,
+}
+
+/// How the local copy of a website changed since the previous run
+/// 
+/// Computed by comparing a run with the `manifest.json` of the run before
+/// it. Without a previous manifest every file counts as added.
+/// 
+/// Files that were kept back from the previous manifest by a failure are
+/// still known from the mapping manifest, so they are removed once their
+/// entry disappears as well.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Changes {
+    /// Files that were not part of the previous run
+    pub added: Vec<ChangedFile>,
+    /// Files whose content changed
+    pub updated: Vec<ChangedFile>,
+    /// Files whose content is the same; in a dry run, every file that was
+    /// part of the previous run, since nothing is fetched
+    pub unchanged: Vec<ChangedFile>,
+    /// Files of the previous run that are no longer listed
+    pub removed: Vec<ChangedFile>,
+    /// What was done with the removed files
+    pub prune: PruneMode,
+    /// Whether this is a preview and nothing was changed
+    pub dry_run: bool,
+}
+
+impl Changes {
+    /// Compare the files of a run with the manifest of the previous run
+    /// 
+    /// A file of the previous run counts as removed when its path is not
+    /// used by this run and its URL was not kept back by a failure or a
+    /// policy, so a file is never removed just because its download failed.
+    /// 
+    /// # Arguments
+    /// * `previous` - The manifest of the previous run, if there was one
+    /// * `mapping` - The mapping manifest, listing every file saved by earlier runs
+    /// * `current` - URL, relative path and SHA-256 of the files that are up to
+    ///   date after this run; the hash is `None` for files that were not fetched
+    /// * `retained` - URLs that are still listed but failed, were skipped or
+    ///   were dropped by section filters
+    /// 
+    /// # Returns
+    /// * `Changes` - The files of both runs, sorted into added, updated,
+    ///   unchanged and removed
+    pub fn compare<'a>(
+        previous: Option<&Manifest>,
+        mapping: &MappingManifest,
+        current: impl IntoIterator<Item = (&'a str, &'a str, Option<&'a str>)>,
+        retained: &HashSet<&str>,
+    ) -> Self {
+        let previous_files: HashMap<&str, &str> = previous
+            .map(|manifest| manifest.files.iter().map(|file| (file.url.as_str(), file.sha256.as_str())).collect())
+            .unwrap_or_default();
+        
+        let mut changes = Changes::default();
+        let mut current_paths = HashSet::new();
+        for (url, path, sha256) in current {
+            current_paths.insert(path.to_string());
+            let file = ChangedFile {
+                url: url.to_string(),
+                path: path.to_string(),
+            };
+            match previous_files.get(url) {
+                None => changes.added.push(file),
+                Some(previous_sha256) if sha256.is_some_and(|sha256| sha256 != *previous_sha256) => {
+                    changes.updated.push(file)
+                }
+                Some(_) => changes.unchanged.push(file),
+            }
+        }
+        
+        let earlier_files = previous
+            .map(|manifest| manifest.files.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|file| (file.url.as_str(), file.path.as_str()))
+            .chain(mapping.files.iter().map(|(path, url)| (url.as_str(), path.as_str())));
+        for (url, path) in earlier_files {
+            if !retained.contains(url) && current_paths.insert(path.to_string()) {
+                changes.removed.push(ChangedFile {
+                    url: url.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+        
+        changes
+    }
+
+    /// Check if anything was added, updated or removed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    /// Describe what was done with the removed files
+    pub fn removed_action(&self) -> &'static str {
+        match (self.prune, self.dry_run) {
+            (PruneMode::Keep, _) => "kept",
+            (PruneMode::Delete, false) => "deleted",
+            (PruneMode::Delete, true) => "would be deleted",
+            (PruneMode::Quarantine, false) => "moved to .llmsdl-removed/",
+            (PruneMode::Quarantine, true) => "would be moved to .llmsdl-removed/",
+        }
+    }
+
+    /// Add the changes of another website
+    pub fn merge(&mut self, other: Changes) {
+        self.added.extend(other.added);
+        self.updated.extend(other.updated);
+        self.unchanged.extend(other.unchanged);
+        self.removed.extend(other.removed);
+        self.prune = other.prune;
+        self.dry_run |= other.dry_run;
+    }
+}
+
+impl fmt::Display for Changes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🔁 Changes since the last run:")?;
+        writeln!(f, "   ➕ Added: {}", self.added.len())?;
+        writeln!(f, "   ✏️  Updated: {}", self.updated.len())?;
+        writeln!(f, "   ♻️  Unchanged: {}", self.unchanged.len())?;
+        writeln!(f, "   ➖ Removed: {}", self.removed.len())?;
+        if self.dry_run {
+            writeln!(f, "   🔍 Dry run: files already present were not checked for updates")?;
+        }
+        
+        // Downloaded files are listed by the download summary already
+        if self.dry_run && !self.added.is_empty() {
+            writeln!(f, "\n➕ Would be added:")?;
+            for file in &self.added {
+                writeln!(f, "   • {}", file.path)?;
+            }
+        }
+        
+        if !self.removed.is_empty() {
+            writeln!(f, "\n➖ No longer listed ({}):", self.removed_action())?;
+            for file in &self.removed {
+                writeln!(f, "   • {}", file.path)?;
+                writeln!(f, "     ← {}", file.url)?;
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Delete or quarantine a file of the previous run that is no longer listed
+/// 
+/// Only regular files inside the domain directory are touched; the
+/// bookkeeping files of llmsdl and the quarantine directory itself are
+/// never pruned. Directories left empty are removed as well. A file that
+/// is already gone is not an error. A quarantined file never replaces an
+/// earlier quarantined copy, it gets a `~N` suffix instead.
+/// 
+/// # Arguments
+/// * `domain_dir` - The domain directory the file is in
+/// * `relative` - The path of the file relative to it, as in the manifest
+/// * `mode` - Whether to delete or quarantine the file
+pub(crate) fn prune_file(domain_dir: &Path, relative: &str, mode: PruneMode) -> Result<(), DownloadError> {
+    let local_path = local_path(domain_dir, relative)?;
+    match fs::symlink_metadata(&local_path) {
+        Ok(metadata) if metadata.is_file() => {}
+        Ok(_) => {
+            return Err(DownloadError::UnsafePath(format!(
+                "{relative} is not a regular file, not pruning it"
+            )));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+    
+    match mode {
+        PruneMode::Keep => return Ok(()),
+        PruneMode::Delete => fs::remove_file(&local_path)?,
+        PruneMode::Quarantine => {
+            let target = quarantine_path(&local_path_in(&domain_dir.join(QUARANTINE_DIR), relative));
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&local_path, &target)?;
+        }
+    }
+    
+    // Directories only exist for the files in them
+    for directory in local_path.ancestors().skip(1).take_while(|ancestor| *ancestor != domain_dir) {
+        if fs::remove_dir(directory).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Find a free path in the quarantine directory, adding `~2`, `~3`, ... to
+/// the file name while the path is taken
+fn quarantine_path(target: &Path) -> PathBuf {
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let (stem, extension) = split_extension(&file_name);
+    
+    let mut path = target.to_path_buf();
+    let mut counter = 1;
+    while fs::symlink_metadata(&path).is_ok() {
+        counter += 1;
+        path.set_file_name(format!("{stem}~{counter}{extension}"));
+    }
+    path
+}
+
+/// Resolve a path recorded in the manifest, refusing anything that could
+/// point outside the domain directory or at llmsdl's own files
+fn local_path(domain_dir: &Path, relative: &str) -> Result<PathBuf, DownloadError> {
+    let unsafe_path = |why: &str| DownloadError::UnsafePath(format!("{relative} {why}, not pruning it"));
+    
+    let path = Path::new(relative);
+    if relative.is_empty() || !path.components().all(|component| matches!(component, Component::Normal(_))) {
+        return Err(unsafe_path("is not a relative path inside the domain directory"));
+    }
+    let first = relative.split('/').next().unwrap_or_default();
+    if [MANIFEST_FILE_NAME, CACHE_FILE_NAME, MAPPING_FILE_NAME, QUARANTINE_DIR].contains(&first) {
+        return Err(unsafe_path("belongs to llmsdl"));
+    }
+    
+    let local_path = local_path_in(domain_dir, relative);
+    // Symlinked directories must not lead outside of the domain directory
+    let inside = match (domain_dir.canonicalize(), local_path.parent().map(Path::canonicalize)) {
+        (Ok(domain_dir), Some(Ok(parent))) => parent.starts_with(domain_dir),
+        _ => true,
+    };
+    if !inside {
+        return Err(unsafe_path("is outside of the domain directory"));
+    }
+    Ok(local_path)
+}
+
+/// Join a `/`-separated relative path onto a directory
+fn local_path_in(directory: &Path, relative: &str) -> PathBuf {
+    relative.split('/').fold(directory.to_path_buf(), |path, component| path.join(component))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::ManifestFile;
+    
+    const A: &str = "https://example.com/a.md";
+    const B: &str = "https://example.com/b.md";
+    const C: &str = "https://example.com/c.md";
+
+    fn manifest(files: &[(&str, &str, &str)]) -> Manifest {
+        let mut manifest = Manifest::new("https://example.com/llms.txt", String::new());
+        manifest.files = files
+            .iter()
+            .map(|(url, path, sha256)| ManifestFile {
+                url: url.to_string(),
+                path: path.to_string(),
+                size: 0,
+                sha256: sha256.to_string(),
+                content_type: None,
+                status: Some(200),
+                fetched_at: String::new(),
+            })
+            .collect();
+        manifest
+    }
+
+    fn paths(files: &[ChangedFile]) -> Vec<&str> {
+        files.iter().map(|file| file.path.as_str()).collect()
+    }
+    
+    #[test]
+    fn everything_is_added_without_a_previous_run() {
+        let changes = Changes::compare(None, &MappingManifest::default(), [(A, "a.md", Some("1"))], &HashSet::new());
+        
+        assert_eq!(paths(&changes.added), ["a.md"]);
+        assert!(changes.updated.is_empty() && changes.unchanged.is_empty() && changes.removed.is_empty());
+        assert!(!changes.is_empty());
+    }
+
+    #[test]
+    fn sorts_files_into_added_updated_unchanged_and_removed() {
+        let previous = manifest(&[(A, "a.md", "1"), (B, "b.md", "2"), (C, "c.md", "3")]);
+        let current = [(A, "a.md", Some("1")), (B, "b.md", Some("changed")), ("https://example.com/d.md", "d.md", Some("4"))];
+        let changes = Changes::compare(Some(&previous), &MappingManifest::default(), current, &HashSet::new());
+        
+        assert_eq!(paths(&changes.added), ["d.md"]);
+        assert_eq!(paths(&changes.updated), ["b.md"]);
+        assert_eq!(paths(&changes.unchanged), ["a.md"]);
+        assert_eq!(paths(&changes.removed), ["c.md"]);
+        assert_eq!(changes.removed[0].url, C);
+    }
+
+    #[test]
+    fn files_that_were_not_fetched_are_unchanged() {
+        let previous = manifest(&[(A, "a.md", "1")]);
+        let changes = Changes::compare(Some(&previous), &MappingManifest::default(), [(A, "a.md", None)], &HashSet::new());
+        
+        assert_eq!(paths(&changes.unchanged), ["a.md"]);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn retained_files_are_not_removed() {
+        let previous = manifest(&[(A, "a.md", "1"), (B, "b.md", "2")]);
+        let changes = Changes::compare(Some(&previous), &MappingManifest::default(), [], &HashSet::from([B]));
+        
+        assert_eq!(paths(&changes.removed), ["a.md"]);
+    }
+
+    #[test]
+    fn a_path_still_in_use_is_not_removed() {
+        // The entry moved to a new URL that maps to the same file
+        let previous = manifest(&[(A, "a.md", "1")]);
+        let changes = Changes::compare(Some(&previous), &MappingManifest::default(), [(B, "a.md", Some("1"))], &HashSet::new());
+        
+        assert_eq!(paths(&changes.added), ["a.md"]);
+        assert!(changes.removed.is_empty());
+    }
+
+    #[test]
+    fn files_only_in_the_mapping_manifest_are_removed_once() {
+        // c.md was kept back from the previous manifest by a failure
+        let previous = manifest(&[(A, "a.md", "1")]);
+        let mut mapping = MappingManifest::default();
+        mapping.files.insert("a.md".to_string(), A.to_string());
+        mapping.files.insert("c.md".to_string(), C.to_string());
+        let changes = Changes::compare(Some(&previous), &mapping, [], &HashSet::new());
+        
+        assert_eq!(paths(&changes.removed), ["a.md", "c.md"]);
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("llmsdl-sync-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn quarantine_keeps_earlier_copies() {
+        let domain_dir = temp_dir("quarantine");
+        for content in ["first", "second", "third"] {
+            fs::create_dir_all(domain_dir.join("docs")).unwrap();
+            fs::write(domain_dir.join("docs/a.md"), content).unwrap();
+            prune_file(&domain_dir, "docs/a.md", PruneMode::Quarantine).unwrap();
+        }
+        
+        let quarantine = domain_dir.join(QUARANTINE_DIR).join("docs");
+        assert_eq!(fs::read_to_string(quarantine.join("a.md")).unwrap(), "first");
+        assert_eq!(fs::read_to_string(quarantine.join("a~2.md")).unwrap(), "second");
+        assert_eq!(fs::read_to_string(quarantine.join("a~3.md")).unwrap(), "third");
+        // The emptied directory is gone
+        assert!(!domain_dir.join("docs").exists());
+        let _ = fs::remove_dir_all(&domain_dir);
+    }
+}
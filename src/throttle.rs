@@ -0,0 +1,303 @@
+use crate::error::DownloadError;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep_until, Instant};
+use url::Url;
+
+/// Politeness limits applied to every host separately
+/// 
+/// All limits are off by default; a host is then only held back after it
+/// asked to wait with a 429 or 503 response.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HostLimits {
+    /// Maximum request rate per host
+    pub rate: Option<Rate>,
+    /// Maximum number of files fetched from a host at the same time
+    pub max_concurrent: Option<usize>,
+    /// Minimum time between the start of two requests to a host
+    pub min_delay: Option<Duration>,
+}
+
+/// A request rate such as 5 requests per second
+/// 
+/// Parsed from `N/s`, `N/m`, `N/h` or `N/<duration>`, e.g. `5/s` or `1/500ms`.
+/// Up to `requests` requests may be sent in a burst after an idle period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    /// Number of requests allowed per period
+    pub requests: u32,
+    /// Length of the period
+    pub per: Duration,
+}
+
+impl Rate {
+    /// Time it takes to earn back one request
+    fn interval(&self) -> Duration {
+        self.per / self.requests
+    }
+}
+
+impl FromStr for Rate {
+    type Err = DownloadError;
+
+    fn from_str(rate: &str) -> Result<Self, Self::Err> {
+        let invalid = || DownloadError::InvalidConfig(format!("invalid rate '{rate}', expected e.g. 5/s or 100/m"));
+        
+        let (requests, per) = rate.split_once('/').ok_or_else(invalid)?;
+        let requests: u32 = requests.trim().parse().map_err(|_| invalid())?;
+        let per = match per.trim() {
+            "s" | "sec" | "second" => Duration::from_secs(1),
+            "m" | "min" | "minute" => Duration::from_secs(60),
+            "h" | "hour" => Duration::from_secs(3600),
+            per => humantime::parse_duration(per).map_err(|_| invalid())?,
+        };
+        
+        if requests == 0 || per.is_zero() {
+            return Err(invalid());
+        }
+        Ok(Rate { requests, per })
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.requests, humantime::format_duration(self.per))
+    }
+}
+
+/// Scheduling state of a single host
+struct HostState {
+    /// Requests may not start before this time, e.g. after a 429 response
+    paused_until: Option<Instant>,
+    /// Start time of the most recent request (or the time reserved for it)
+    last_request: Option<Instant>,
+    /// `Crawl-delay` requested by the host's robots.txt
+    crawl_delay: Option<Duration>,
+    /// Requests left in the token bucket at `last_refill`
+    tokens: f64,
+    last_refill: Instant,
+    /// Limits how many files are fetched from the host at once
+    slots: Option<Arc<Semaphore>>,
+}
+
+/// Enforces [`HostLimits`] and server-requested pauses for each host
+/// 
+/// Shared by all clones of an [`HttpClient`](crate::HttpClient), so limits
+/// hold across concurrent downloads.
+pub(crate) struct HostThrottle {
+    limits: HostLimits,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl HostThrottle {
+    pub(crate) fn new(limits: HostLimits) -> Self {
+        Self {
+            limits,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a free slot for fetching a file from the host of a URL
+    /// 
+    /// The slot is released when the returned permit is dropped. Without a
+    /// per-host concurrency limit this returns right away.
+    pub(crate) async fn acquire_slot(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let max_concurrent = self.limits.max_concurrent?;
+        let host = host_key(url)?;
+        
+        let slots = self.with_host(host, |state| {
+            Arc::clone(state.slots.get_or_insert_with(|| Arc::new(Semaphore::new(max_concurrent.max(1)))))
+        });
+        slots.acquire_owned().await.ok()
+    }
+
+    /// Wait until the next request to the host of a URL may be sent
+    /// 
+    /// Honors pauses, the minimum delay and the token bucket. The start time
+    /// is reserved before waiting, so concurrent callers queue up in order.
+    pub(crate) async fn wait_turn(&self, url: &str) {
+        let Some(host) = host_key(url) else {
+            return;
+        };
+        let limits = self.limits;
+        
+        let start = self.with_host(host.clone(), |state| {
+            let now = Instant::now();
+            let mut start = now.max(state.paused_until.unwrap_or(now));
+            
+            let min_delay = limits.min_delay.max(state.crawl_delay);
+            if let (Some(min_delay), Some(last_request)) = (min_delay, state.last_request) {
+                start = start.max(last_request + min_delay);
+            }
+            
+            if let Some(rate) = limits.rate {
+                let capacity = f64::from(rate.requests);
+                let interval = rate.interval().as_secs_f64();
+                // Queue up behind requests that already reserved a later start
+                start = start.max(state.last_refill);
+                let elapsed = start.duration_since(state.last_refill).as_secs_f64();
+                let tokens = (state.tokens + elapsed / interval).min(capacity);
+                
+                if tokens >= 1.0 {
+                    state.tokens = tokens - 1.0;
+                } else {
+                    // Wait until the next token is earned, then spend it right away
+                    start += Duration::from_secs_f64((1.0 - tokens) * interval);
+                    state.tokens = 0.0;
+                }
+                state.last_refill = start;
+            }
+            
+            state.last_request = Some(start);
+            start
+        });
+        sleep_until(start).await;
+        
+        // A pause may have started while waiting for the reserved time
+        let paused_until = self.with_host(host, |state| state.paused_until);
+        if let Some(until) = paused_until {
+            sleep_until(until).await;
+        }
+    }
+
+    /// Hold back all requests to the host of a URL for a while
+    pub(crate) fn pause(&self, url: &str, delay: Duration) {
+        let Some(host) = host_key(url) else {
+            return;
+        };
+        
+        let until = Instant::now() + delay;
+        self.with_host(host, |state| {
+            state.paused_until = Some(state.paused_until.map_or(until, |paused| paused.max(until)));
+        });
+    }
+
+    /// Apply a robots.txt `Crawl-delay` to the host of a URL
+    /// 
+    /// The longer of the crawl delay and the configured minimum delay is used.
+    pub(crate) fn set_crawl_delay(&self, url: &str, delay: Duration) {
+        let Some(host) = host_key(url) else {
+            return;
+        };
+        
+        self.with_host(host, |state| state.crawl_delay = Some(delay));
+    }
+
+    /// Run a closure on the state of a host, creating it on first use
+    fn with_host<T>(&self, host: String, f: impl FnOnce(&mut HostState) -> T) -> T {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.entry(host).or_insert_with(|| HostState {
+            paused_until: None,
+            last_request: None,
+            crawl_delay: None,
+            tokens: self.limits.rate.map_or(0.0, |rate| f64::from(rate.requests)),
+            last_refill: Instant::now(),
+            slots: None,
+        });
+        f(state)
+    }
+}
+
+/// Identify the host of a URL, including the port
+fn host_key(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    Some(format!("{}:{}", url.host_str()?, url.port_or_known_default()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const URL: &str = "https://example.com/a.md";
+
+    fn rate(requests: u32, per: Duration) -> Rate {
+        Rate { requests, per }
+    }
+
+    #[test]
+    fn parses_rates() {
+        assert_eq!("5/s".parse::<Rate>().unwrap(), rate(5, Duration::from_secs(1)));
+        assert_eq!(" 100 / min ".parse::<Rate>().unwrap(), rate(100, Duration::from_secs(60)));
+        assert_eq!("2/hour".parse::<Rate>().unwrap(), rate(2, Duration::from_secs(3600)));
+        assert_eq!("1/500ms".parse::<Rate>().unwrap(), rate(1, Duration::from_millis(500)));
+        assert_eq!("3/2m".parse::<Rate>().unwrap(), rate(3, Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn rejects_invalid_rates() {
+        for value in ["", "5", "5/", "/s", "0/s", "5/0s", "-1/s", "1.5/s", "5/fortnight"] {
+            assert!(matches!(value.parse::<Rate>(), Err(DownloadError::InvalidConfig(_))), "{value}");
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let rate = rate(5, Duration::from_millis(1500));
+        assert_eq!(rate.to_string(), "5/1s 500ms");
+        assert_eq!(rate.to_string().parse::<Rate>().unwrap(), rate);
+    }
+
+    /// Time from now until each of the given URLs gets its turn, in order
+    async fn turns(throttle: &HostThrottle, urls: &[&str]) -> Vec<Duration> {
+        let start = Instant::now();
+        let mut turns = Vec::new();
+        for url in urls {
+            throttle.wait_turn(url).await;
+            turns.push(start.elapsed());
+        }
+        turns
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn token_bucket_allows_a_burst_then_spaces_requests() {
+        let throttle = HostThrottle::new(HostLimits {
+            rate: Some(rate(2, Duration::from_secs(1))),
+            ..HostLimits::default()
+        });
+        
+        let turns = turns(&throttle, &[URL, URL, URL, URL]).await;
+        assert_eq!(turns, [0, 0, 500, 1000].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn token_bucket_refills_while_idle() {
+        let throttle = HostThrottle::new(HostLimits {
+            rate: Some(rate(2, Duration::from_secs(1))),
+            ..HostLimits::default()
+        });
+        turns(&throttle, &[URL, URL]).await;
+        
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        // The bucket holds at most two requests, however long it was idle
+        let turns = turns(&throttle, &[URL, URL, URL]).await;
+        assert_eq!(turns, [0, 0, 500].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_apply_to_each_host_separately() {
+        let throttle = HostThrottle::new(HostLimits {
+            rate: Some(rate(1, Duration::from_secs(1))),
+            ..HostLimits::default()
+        });
+        
+        let other = "https://other.example/a.md";
+        let turns = turns(&throttle, &[URL, other, "https://example.com:8443/b.md"]).await;
+        assert_eq!(turns, [Duration::ZERO; 3]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn min_delay_and_pauses_hold_back_requests() {
+        let throttle = HostThrottle::new(HostLimits {
+            min_delay: Some(Duration::from_millis(200)),
+            ..HostLimits::default()
+        });
+        assert_eq!(turns(&throttle, &[URL, URL]).await, [0, 200].map(Duration::from_millis));
+        
+        throttle.pause(URL, Duration::from_secs(3));
+        assert_eq!(turns(&throttle, &[URL]).await, [Duration::from_secs(3)]);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::http_client::HttpClient;
+use crate::parser::{resolve_url, CodeFences};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::fmt;
+use url::Url;
+
+/// How serious a lint issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file violates the llms.txt spec or will not download correctly
+    Error,
+    /// The file works but deviates from the spec's recommendations
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single problem found in an llms.txt file
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number, counted in characters
+    pub column: usize,
+    /// How serious the issue is
+    pub severity: Severity,
+    /// Stable identifier for the kind of issue, e.g. `missing-h1`
+    pub code: &'static str,
+    /// Human-readable description of the issue
+    pub message: String,
+}
+
+impl LintIssue {
+    /// Create a new issue at the given position
+    pub fn new(line: usize, column: usize, severity: Severity, code: &'static str, message: String) -> Self {
+        Self { line, column, severity, code, message }
+    }
+}
+
+/// A link target found while linting, kept for dead link checks
+#[derive(Debug, Clone)]
+struct LinkTarget {
+    line: usize,
+    column: usize,
+    target: String,
+}
+
+/// Result of validating an llms.txt file
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport {
+    /// Where the llms.txt content came from (file path or URL)
+    pub source: String,
+    /// Issues found, ordered by position in the file
+    pub issues: Vec<LintIssue>,
+    /// Links the downloader would fetch
+    #[serde(skip)]
+    links: Vec<LinkTarget>,
+}
+
+impl LintReport {
+    /// Get the number of errors
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+
+    /// Get the number of warnings
+    pub fn warning_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning).count()
+    }
+
+    /// Check if any errors were found
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Get the number of links the downloader would fetch
+    pub fn link_count(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Check every link with a HEAD request and report the unreachable ones
+    /// 
+    /// # Arguments
+    /// * `client` - The HTTP client used for the requests
+    /// * `base_url` - The base URL to resolve relative links against
+    /// * `max_concurrent` - Maximum number of requests in flight
+    pub async fn check_links(&mut self, client: &HttpClient, base_url: &Url, max_concurrent: usize) {
+        let checks = stream::iter(self.links.clone())
+            .map(|link| async move {
+                let outcome = match resolve_url(&link.target, base_url) {
+                    Ok(url) => client.check_url(&url).await,
+                    Err(e) => Err(e),
+                };
+                (link, outcome)
+            })
+            .buffer_unordered(max_concurrent.max(1))
+            .collect::<Vec<_>>()
+            .await;
+        
+        for (link, outcome) in checks {
+            if let Err(e) = outcome {
+                self.issues.push(LintIssue::new(
+                    link.line,
+                    link.column,
+                    Severity::Error,
+                    "dead-link",
+                    format!("Link '{}' is not reachable: {e}", link.target),
+                ));
+            }
+        }
+        
+        self.sort_issues();
+    }
+
+    /// Keep issues ordered by their position in the file
+    fn sort_issues(&mut self) {
+        self.issues.sort_by_key(|issue| (issue.line, issue.column));
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(
+                f,
+                "{}:{}:{}: {}[{}]: {}",
+                self.source, issue.line, issue.column, issue.severity, issue.code, issue.message
+            )?;
+        }
+        
+        if !self.issues.is_empty() {
+            writeln!(f)?;
+        }
+        
+        if self.has_errors() {
+            write!(f, "❌ ")?;
+        } else if self.warning_count() > 0 {
+            write!(f, "⚠️  ")?;
+        } else {
+            write!(f, "✅ ")?;
+        }
+        write!(
+            f,
+            "{}: {} error(s), {} warning(s), {} link(s)",
+            self.source,
+            self.error_count(),
+            self.warning_count(),
+            self.link_count()
+        )
+    }
+}
+
+/// Validate llms.txt content against the llmstxt.org spec
+/// 
+/// Reports, with line and column numbers:
+/// - a missing or misplaced H1 title
+/// - blockquotes anywhere but directly below the title
+/// - malformed markdown links in the list items of sections
+/// - entries the downloader ignores because their target has no dot
+/// - empty sections
+/// 
+/// # Arguments
+/// * `content` - The raw content of the llms.txt file
+/// * `source` - Where the content came from, used in the report
+/// 
+/// # Returns
+/// * `LintReport` - The issues found
+pub fn validate_llms_txt(content: &str, source: &str) -> LintReport {
+    let mut report = LintReport {
+        source: source.to_string(),
+        issues: Vec::new(),
+        links: Vec::new(),
+    };
+    
+    let mut title_line: Option<usize> = None;
+    let mut seen_content = false;
+    let mut fences = CodeFences::default();
+    let mut previous: Option<&str> = None;
+    let mut section: Option<(usize, usize)> = None; // (heading line, entry count)
+    let mut in_sections = false;
+    let mut issues = Vec::new();
+    
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+        let indent = column_of(line, line.len() - line.trim_start().len());
+        let issues_before = issues.len();
+        
+        if fences.is_fence(line) {
+            previous = Some(trimmed);
+            seen_content = true;
+            continue;
+        }
+        if fences.in_code_block() {
+            continue;
+        }
+        
+        if trimmed.is_empty() {
+            continue;
+        }
+        
+        if let Some(title) = trimmed.strip_prefix("# ") {
+            if title.trim().is_empty() {
+                issues.push(LintIssue::new(line_number, indent, Severity::Error, "empty-h1", "H1 title is empty".to_string()));
+            }
+            match title_line {
+                Some(first) => issues.push(LintIssue::new(
+                    line_number,
+                    indent,
+                    Severity::Warning,
+                    "multiple-h1",
+                    format!("Only one H1 title is allowed, the first one is on line {first}"),
+                )),
+                None if seen_content => issues.push(LintIssue::new(
+                    line_number,
+                    indent,
+                    Severity::Error,
+                    "h1-not-first",
+                    "The H1 title must be the first line of the file".to_string(),
+                )),
+                None => {}
+            }
+            title_line.get_or_insert(line_number);
+        } else if let Some(name) = trimmed.strip_prefix("## ").or(if trimmed == "##" { Some("") } else { None }) {
+            close_section(section.take(), &mut issues);
+            if name.trim().is_empty() {
+                issues.push(LintIssue::new(line_number, indent, Severity::Warning, "empty-section-name", "H2 section heading is empty".to_string()));
+            }
+            section = Some((line_number, 0));
+            in_sections = true;
+        } else if trimmed.starts_with('>') {
+            // The summary blockquote must directly follow the title
+            let follows_title = previous.is_some_and(|prev| prev.starts_with("# ") || prev.starts_with('>'));
+            if in_sections || !follows_title {
+                issues.push(LintIssue::new(
+                    line_number,
+                    indent,
+                    Severity::Warning,
+                    "misplaced-blockquote",
+                    "The summary blockquote should directly follow the H1 title".to_string(),
+                ));
+            }
+        } else if let Some(link) = lint_line(line, line_number, in_sections, &mut issues) {
+            if !link.target.contains('.') {
+                issues.push(LintIssue::new(
+                    link.line,
+                    link.column,
+                    Severity::Warning,
+                    "ignored-entry",
+                    format!("Link target '{}' has no file extension and will be ignored by the downloader", link.target),
+                ));
+            } else {
+                report.links.push(link);
+                if let Some((_, count)) = section.as_mut() {
+                    *count += 1;
+                }
+            }
+        } else if in_sections && is_list_item(trimmed) && issues.len() == issues_before {
+            issues.push(LintIssue::new(
+                line_number,
+                indent,
+                Severity::Warning,
+                "not-a-link",
+                "List items in sections should be markdown links: - [Title](url): description".to_string(),
+            ));
+        }
+        
+        seen_content = true;
+        previous = Some(trimmed);
+    }
+    close_section(section, &mut issues);
+    
+    if title_line.is_none() {
+        issues.push(LintIssue::new(1, 1, Severity::Error, "missing-h1", "llms.txt must start with an H1 title (# Project name)".to_string()));
+    }
+    
+    report.issues = issues;
+    report.sort_issues();
+    report
+}
+
+/// Report a section that ended without any downloadable entries
+fn close_section(section: Option<(usize, usize)>, issues: &mut Vec<LintIssue>) {
+    if let Some((line, 0)) = section {
+        issues.push(LintIssue::new(
+            line,
+            1,
+            Severity::Warning,
+            "empty-section",
+            "Section does not list any downloadable files".to_string(),
+        ));
+    }
+}
+
+/// Check a line for markdown links or plain paths
+/// 
+/// Only list items inside H2 sections are checked as markdown links, links
+/// in prose such as the details paragraphs are left alone. Malformed links
+/// are reported to `issues`. Returns the link target if the line contains
+/// one that the downloader would pick up.
+fn lint_line(line: &str, line_number: usize, in_sections: bool, issues: &mut Vec<LintIssue>) -> Option<LinkTarget> {
+    let trimmed = line.trim();
+    let mut malformed = |byte_index: usize, message: &str| {
+        issues.push(LintIssue::new(
+            line_number,
+            column_of(line, byte_index),
+            Severity::Error,
+            "malformed-link",
+            message.to_string(),
+        ));
+        None
+    };
+    
+    let Some(open) = line.find('[') else {
+        // Plain file paths are accepted outside of list items
+        if trimmed.contains('.') && !is_list_item(trimmed) && !trimmed.contains(char::is_whitespace) {
+            let start = line.len() - line.trim_start().len();
+            return Some(LinkTarget {
+                line: line_number,
+                column: column_of(line, start),
+                target: trimmed.to_string(),
+            });
+        }
+        return None;
+    };
+    if !in_sections || !is_list_item(trimmed) {
+        return None;
+    }
+    
+    let Some(close) = line[open..].find(']').map(|offset| open + offset) else {
+        return malformed(open, "Unclosed '[' in markdown link");
+    };
+    
+    let after_close = &line[close + 1..];
+    if !after_close.starts_with('(') {
+        if after_close.trim_start().starts_with('(') {
+            return malformed(close + 1, "Unexpected whitespace between ']' and '(' in markdown link");
+        }
+        return malformed(close, "Expected '(' after ']' in markdown link");
+    }
+    
+    let target_start = close + 2;
+    let Some(target_end) = line[target_start..].find(')').map(|offset| target_start + offset) else {
+        return malformed(close + 1, "Unclosed '(' in markdown link");
+    };
+    
+    if line[open + 1..close].trim().is_empty() {
+        return malformed(open, "Markdown link has an empty title");
+    }
+    
+    let target = line[target_start..target_end].trim();
+    if target.is_empty() {
+        return malformed(close + 1, "Markdown link has an empty URL");
+    }
+    if target.contains(char::is_whitespace) {
+        return malformed(target_start, "Markdown link URL contains whitespace");
+    }
+    
+    let rest = line[target_end + 1..].trim();
+    if !rest.is_empty() && !rest.starts_with(':') {
+        issues.push(LintIssue::new(
+            line_number,
+            column_of(line, target_end + 1),
+            Severity::Warning,
+            "description-separator",
+            "Link descriptions should be separated from the link by ': '".to_string(),
+        ));
+    }
+    
+    Some(LinkTarget {
+        line: line_number,
+        column: column_of(line, target_start),
+        target: target.to_string(),
+    })
+}
+
+/// Check if a line is a markdown list item
+fn is_list_item(trimmed: &str) -> bool {
+    trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ")
+}
+
+/// Convert a byte index within a line to a 1-based character column
+fn column_of(line: &str, byte_index: usize) -> usize {
+    line[..byte_index].chars().count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(content: &str) -> Vec<&'static str> {
+        validate_llms_txt(content, "llms.txt").issues.iter().map(|issue| issue.code).collect()
+    }
+
+    #[test]
+    fn links_in_details_are_not_linted() {
+        let content = "# Site\n\n> Summary\n\nSee [RFC 9309] for details.\n\n## Docs\n\n- [A](/a.md)\n";
+        assert!(codes(content).is_empty(), "{:?}", codes(content));
+    }
+
+    #[test]
+    fn malformed_list_items_in_sections_are_reported() {
+        let content = "# Site\n\n## Docs\n\n- [A](/a.md)\n- [B] (/b.md)\n";
+        assert_eq!(codes(content), ["malformed-link"]);
+    }
+
+    #[test]
+    fn code_blocks_end_at_a_matching_fence() {
+        // The tildes are code, so the broken link below them is too
+        let content = "# Site\n\n## Docs\n\n- [A](/a.md)\n\n```\n~~~\n- [B] (/b.md)\n```\n\n- [C] (/c.md)\n";
+        let issues = validate_llms_txt(content, "llms.txt").issues;
+        let lines: Vec<_> = issues.iter().map(|issue| (issue.line, issue.code)).collect();
+        assert_eq!(lines, [(12, "malformed-link")]);
+    }
+}